@@ -0,0 +1,346 @@
+use crate::expression::is_same;
+use crate::lox_instance::LoxInstance;
+use crate::token::{Token, TokenType};
+use crate::lox_error::{LoxError, LoxResult};
+use crate::{callable::Callable, interpreter::Interpreter, token::{AtomicLiteral, Literal}};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn expect_map(value: &Literal, function: &str) -> LoxResult<Rc<RefCell<crate::lox_map::LoxMap>>> {
+    match value {
+        Literal::Map(map) => Ok(map.clone()),
+        _ => Err(LoxError::RuntimeError {
+            token: None,
+            message: format!("{}() expects a map", function),
+        }),
+    }
+}
+
+fn expect_instance(value: &Literal, function: &str) -> LoxResult<LoxInstance> {
+    match value {
+        Literal::Instance(instance) => Ok(instance.clone()),
+        _ => Err(LoxError::RuntimeError {
+            token: None,
+            message: format!("{}() expects an instance", function),
+        }),
+    }
+}
+
+// private `#names` can't be reached by name from outside, not even through these
+fn expect_name(value: &Literal, function: &str) -> LoxResult<String> {
+    match value {
+        Literal::Basic(AtomicLiteral::String(name)) if name.starts_with('#') => Err(LoxError::RuntimeError {
+            token: None,
+            message: format!("{}() can't reach private member {}", function, name),
+        }),
+        Literal::Basic(AtomicLiteral::String(name)) => Ok(name.clone()),
+        _ => Err(LoxError::RuntimeError {
+            token: None,
+            message: format!("{}() expects a field name string", function),
+        }),
+    }
+}
+
+fn string_list(names: Vec<String>) -> Literal {
+    let items = names.into_iter().map(|name| Literal::Basic(AtomicLiteral::String(name))).collect();
+    Literal::List(Rc::new(RefCell::new(items)))
+}
+
+pub struct Len;
+
+impl Callable for Len {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let len = match &args[0] {
+            Literal::List(items) => items.borrow().len(),
+            Literal::Map(map) => map.borrow().len(),
+            Literal::Basic(AtomicLiteral::String(s)) => s.chars().count(),
+            _ => {
+                return Err(LoxError::RuntimeError {
+                    token: None,
+                    message: "len() expects a list, map or string".to_string(),
+                })
+            }
+        };
+        Ok(Literal::Basic(AtomicLiteral::Number(len as i32)))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct Keys;
+
+impl Callable for Keys {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let map = expect_map(&args[0], "keys")?;
+        let keys = map
+            .borrow()
+            .entries()
+            .iter()
+            .map(|(key, _)| Literal::Basic(key.clone()))
+            .collect();
+        Ok(Literal::List(Rc::new(RefCell::new(keys))))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct Values;
+
+impl Callable for Values {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let map = expect_map(&args[0], "values")?;
+        let values = map
+            .borrow()
+            .entries()
+            .iter()
+            .map(|(_, value)| value.clone())
+            .collect();
+        Ok(Literal::List(Rc::new(RefCell::new(values))))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct HasKey;
+
+impl Callable for HasKey {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let map = expect_map(&args[0], "hasKey")?;
+        let found = match crate::expression::map_key(&args[1]) {
+            Some(key) => map.borrow().contains(&key),
+            None => false,
+        };
+        Ok(Literal::Basic(AtomicLiteral::Bool(found)))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// strict identity, unlike == it never calls __eq__ and never treats 1 and 1.0 as equal
+pub struct Same;
+
+impl Callable for Same {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        Ok(Literal::Basic(AtomicLiteral::Bool(is_same(&args[0], &args[1]))))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct Type;
+
+impl Callable for Type {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let name = match &args[0] {
+            Literal::Basic(AtomicLiteral::Nil) => "nil",
+            Literal::Basic(AtomicLiteral::Bool(_)) => "bool",
+            Literal::Basic(AtomicLiteral::Number(_)) => "int",
+            Literal::Basic(AtomicLiteral::Float(_)) => "float",
+            Literal::Basic(AtomicLiteral::String(_)) => "string",
+            Literal::List(_) => "list",
+            Literal::Map(_) => "map",
+            Literal::Instance(_) => "instance",
+            Literal::Module(_) => "module",
+            Literal::Trait(_) => "trait",
+            callable if callable.class_ref().is_some() => "class",
+            Literal::LoxCallable(_) => "function",
+        };
+        Ok(Literal::Basic(AtomicLiteral::String(name.to_string())))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct Fields;
+
+impl Callable for Fields {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        Ok(string_list(expect_instance(&args[0], "fields")?.field_names()))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// only fields count, getters and methods don't
+pub struct HasField;
+
+impl Callable for HasField {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let instance = expect_instance(&args[0], "hasField")?;
+        let name = expect_name(&args[1], "hasField")?;
+        Ok(Literal::Basic(AtomicLiteral::Bool(instance.has_field(&name))))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// takes an instance or a class, inherited methods are listed too
+pub struct Methods;
+
+impl Callable for Methods {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let names = match (&args[0], args[0].class_ref()) {
+            (Literal::Instance(instance), _) => instance.class().method_names(),
+            (_, Some(class)) => class.method_names(),
+            _ => {
+                return Err(LoxError::RuntimeError {
+                    token: None,
+                    message: "methods() expects an instance or a class".to_string(),
+                })
+            }
+        };
+        Ok(string_list(names))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub struct ClassName;
+
+impl Callable for ClassName {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let name = match (&args[0], args[0].class_ref()) {
+            (Literal::Instance(instance), _) => instance.class_name().to_string(),
+            (_, Some(class)) => class.name().to_string(),
+            _ => {
+                return Err(LoxError::RuntimeError {
+                    token: None,
+                    message: "className() expects an instance or a class".to_string(),
+                })
+            }
+        };
+        Ok(Literal::Basic(AtomicLiteral::String(name)))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// obj.name with the name computed at runtime, getters and methods are found like with `.`
+pub struct GetField;
+
+impl Callable for GetField {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let instance = expect_instance(&args[0], "getField")?;
+        let name = expect_name(&args[1], "getField")?;
+        if !instance.has_property(&name) {
+            return Err(LoxError::RuntimeError {
+                token: None,
+                message: format!("Undefined property {}", name),
+            });
+        }
+        instance.get(Token::new(TokenType::IDENTIFIER, name, 0, AtomicLiteral::Nil), interpreter)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// obj.name = value with the name computed at runtime, setters run like with `.`
+pub struct SetField;
+
+impl Callable for SetField {
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, mut args: Vec<Literal>) -> LoxResult<Literal> {
+        let value = args.pop().unwrap_or(Literal::Basic(AtomicLiteral::Nil));
+        let instance = expect_instance(&args[0], "setField")?;
+        let name = expect_name(&args[1], "setField")?;
+        instance.set(Token::new(TokenType::IDENTIFIER, name, 0, AtomicLiteral::Nil), value.clone(), interpreter)?;
+        Ok(value)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// nil for a class without a superclass
+pub struct SuperclassOf;
+
+impl Callable for SuperclassOf {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, _: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        let class = args[0].class_ref().ok_or_else(|| LoxError::RuntimeError {
+            token: None,
+            message: "superclassOf() expects a class".to_string(),
+        })?;
+        Ok(match class.superclass() {
+            Some(superclass) => Literal::LoxCallable(superclass.clone()),
+            None => Literal::Basic(AtomicLiteral::Nil),
+        })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
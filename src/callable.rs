@@ -1,10 +1,44 @@
 use crate::{interpreter::Interpreter, token::Literal};
 use crate::lox_error::LoxResult;
+use std::rc::Rc;
 
 
 pub trait Callable {
     fn arity(&self) ->usize;
     fn call(&self, env:&mut Interpreter ,args:Vec<Literal>) -> LoxResult<Literal>;
     fn as_any(&self) -> &dyn std::any::Any;
+    fn describe(&self) -> String {
+        "<native fn>".to_string()
+    }
+}
+
+// a built-in as it is bound in the globals, it knows the name it was registered under so it prints like `<native fn len/1>`
+pub struct Native {
+    name: &'static str,
+    function: Rc<dyn Callable>,
+}
+
+impl Native {
+    pub fn new(name: &'static str, function: Rc<dyn Callable>) -> Self {
+        Native { name, function }
+    }
+}
+
+impl Callable for Native {
+    fn arity(&self) -> usize {
+        self.function.arity()
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Literal>) -> LoxResult<Literal> {
+        self.function.call(interpreter, args)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.function.as_any()
+    }
+
+    fn describe(&self) -> String {
+        format!("<native fn {}/{}>", self.name, self.function.arity())
+    }
 }
 
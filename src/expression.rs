@@ -4,6 +4,14 @@ use crate::lox_error::LoxError;
 use crate::lox_error::LoxResult;
 use crate::token::Literal;
 use crate::interpreter::{Interpreter};
+use crate::callable::Callable;
+use crate::lox_class::LoxClass;
+use crate::lox_instance::LoxInstance;
+use crate::lox_map::LoxMap;
+use crate::loxfuncs::LoxFunction;
+use crate::statement::FunctionProps;
+use std::cell::RefCell;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 #[derive(Clone,PartialEq, Eq, Hash)]
@@ -17,23 +25,42 @@ pub enum ExpressionType {
     Call(CallArgs),
     Get(GetArgs),
     Set(SetArgs),
+    // `this.name = value` for a `let` default in a class body, stored as a plain field so getters and setters don't run
+    FieldDefault(SetArgs),
     Super(SuperArgs),
     Variable(Token),
     Assignment(AssignExpression),
     Postfix(PostfixExpression),
+    List(ListArgs),
+    Index(IndexArgs),
+    IndexSet(IndexSetArgs),
+    Map(MapArgs),
+    Lambda(LambdaArgs),
+    CompoundAssign(CompoundAssignArgs),
+    Conditional(ConditionalArgs),
+    // a postfix chain with at least one `?.` in it, evaluates to nil once any `?.` meets nil
+    OptionalChain(Box<ExpressionType>),
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub enum FunctionType {
     Function,
     None,
     Method,
-    Initializer
+    Initializer,
+    // the method the parser builds from a class body's `let` defaults
+    FieldInitializer
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub enum LoopType {
+    None,
+    Loop
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub enum ClassType {
     None,
     Class,
-    SubClass
+    SubClass,
+    Trait
 }
 
 #[derive(Clone,PartialEq, Eq, Hash)]
@@ -44,7 +71,16 @@ pub struct SuperArgs {
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub struct GetArgs {
     pub name: Token,
-    pub object: Box<ExpressionType>
+    pub object: Box<ExpressionType>,
+    // obj?.name, a nil object ends the whole optional chain with nil instead of an error
+    pub optional: bool
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub struct ConditionalArgs {
+    pub condition: Box<ExpressionType>,
+    pub question: Token,
+    pub then_branch: Box<ExpressionType>,
+    pub else_branch: Box<ExpressionType>
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub struct SetArgs {
@@ -53,6 +89,45 @@ pub struct SetArgs {
     pub value: Box<ExpressionType>
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
+pub struct ListArgs {
+    pub bracket: Token,
+    pub elements: Vec<ExpressionType>
+}
+#[derive(Clone)]
+pub struct LambdaArgs {
+    pub function: Rc<FunctionProps>
+}
+// every lambda node is its own function, so node identity is the identity of the shared declaration
+impl PartialEq for LambdaArgs {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.function, &other.function)
+    }
+}
+impl Eq for LambdaArgs {}
+impl Hash for LambdaArgs {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.function).hash(state);
+    }
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub struct MapArgs {
+    pub brace: Token,
+    pub entries: Vec<(ExpressionType, ExpressionType)>
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub struct IndexArgs {
+    pub object: Box<ExpressionType>,
+    pub bracket: Token,
+    pub index: Box<ExpressionType>
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub struct IndexSetArgs {
+    pub object: Box<ExpressionType>,
+    pub bracket: Token,
+    pub index: Box<ExpressionType>,
+    pub value: Box<ExpressionType>
+}
+#[derive(Clone,PartialEq, Eq, Hash)]
 pub struct CallArgs {
     pub callee: Box<ExpressionType>,
     pub paren: Token,
@@ -63,20 +138,27 @@ pub struct AssignExpression {
     pub name: Token,
     pub value: Box<ExpressionType>,
 }
+// `target op= value`, prefix `++x` / `--x` are parsed into this with a value of 1
+#[derive(Clone,PartialEq, Eq, Hash)]
+pub struct CompoundAssignArgs {
+    pub target: Box<ExpressionType>,
+    pub operator: Token,
+    pub value: Box<ExpressionType>,
+}
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub struct BinaryExpression {
     pub left: Box<ExpressionType>,
-    pub operator: TokenType,
+    pub operator: Token,
     pub right: Box<ExpressionType>,
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub struct UnaryExpression {
-    pub operator: TokenType,
+    pub operator: Token,
     pub right: Box<ExpressionType>,
 }
 #[derive(Clone,PartialEq, Eq, Hash)]
 pub struct PostfixExpression {
-    pub operator: TokenType,
+    pub operator: Token,
     pub expr: Box<ExpressionType>,
 }
 
@@ -111,14 +193,54 @@ impl Resolvable for ExpressionType {
                 resolver.resolve(&*logic.left)?;
                 resolver.resolve(&*logic.right)?;
             },
+            ExpressionType::OptionalChain(chain) => resolver.resolve(chain.as_ref())?,
+            ExpressionType::Conditional(conditional) => {
+                resolver.resolve(&*conditional.condition)?;
+                resolver.resolve(&*conditional.then_branch)?;
+                resolver.resolve(&*conditional.else_branch)?;
+            },
             ExpressionType::Unary(unary) => {
                 resolver.resolve(&*unary.right)?;
             },
-            ExpressionType::Get(get ) => resolver.resolve(get.object.as_ref())?,
-            ExpressionType::Set(set) => {
+            ExpressionType::Get(get ) => {
+                resolver.check_private(&get.name)?;
+                resolver.resolve(get.object.as_ref())?
+            },
+            ExpressionType::Set(set) | ExpressionType::FieldDefault(set) => {
+                resolver.check_private(&set.name)?;
                 resolver.resolve(set.object.as_ref())?;
                 resolver.resolve(set.value.as_ref())?;
             },
+            ExpressionType::List(list) => {
+                for element in &list.elements {
+                    resolver.resolve(element)?;
+                }
+            },
+            ExpressionType::Lambda(lambda) => {
+                resolver.resolve_function(&lambda.function, FunctionType::Function)?;
+            },
+            ExpressionType::CompoundAssign(compound) => {
+                resolver.resolve(compound.value.as_ref())?;
+                resolver.resolve(compound.target.as_ref())?;
+            },
+            ExpressionType::Postfix(post) => {
+                resolver.resolve(post.expr.as_ref())?;
+            },
+            ExpressionType::Map(map) => {
+                for (key, value) in &map.entries {
+                    resolver.resolve(key)?;
+                    resolver.resolve(value)?;
+                }
+            },
+            ExpressionType::Index(index) => {
+                resolver.resolve(index.object.as_ref())?;
+                resolver.resolve(index.index.as_ref())?;
+            },
+            ExpressionType::IndexSet(index) => {
+                resolver.resolve(index.object.as_ref())?;
+                resolver.resolve(index.index.as_ref())?;
+                resolver.resolve(index.value.as_ref())?;
+            },
             ExpressionType::This(this) => {
                 if resolver.current_class == ClassType::None {
                     return Err(LoxError::ParseError {
@@ -135,16 +257,89 @@ impl Resolvable for ExpressionType {
                     ClassType::Class => {
                         Err(LoxError::RuntimeError { token: Some(superb.keyword.clone()), message: String::from("Cannot use super in a class with no superclass") })?
                     }
-                    ClassType::SubClass => resolver.resolve_local(self, &superb.keyword)?
+                    ClassType::Trait => {
+                        Err(LoxError::RuntimeError { token: Some(superb.keyword.clone()), message: String::from("Cannot use super in a trait") })?
+                    }
+                    ClassType::SubClass if superb.method.lexeme.starts_with('#') => {
+                        Err(LoxError::RuntimeError { token: Some(superb.method.clone()), message: String::from("Private members can't be reached through super") })?
+                    }
+                    ClassType::SubClass if superb.method.lexeme == "init" && resolver.current_function == FunctionType::FieldInitializer => {
+                        Err(LoxError::RuntimeError { token: Some(superb.method.clone()), message: String::from("Cannot call super.init from a field default") })?
+                    }
+                    ClassType::SubClass => {
+                        if superb.method.lexeme == "init" {
+                            resolver.calls_super_init = true;
+                        }
+                        resolver.resolve_local(self, &superb.keyword)?
+                    }
                 }
             }
-            _ => {}
         }
         Ok(())
     }
 }
 
 
+// errors raised without a location, by natives or class instantiation, point at the call. errors that escape
+// a constructor keep where they were raised and also say where the instance was created
+fn call_site_error(error: LoxError, paren: &Token, class: Option<&LoxClass>) -> LoxError {
+    let called_from = |class: &LoxClass| format!(" (constructing {}, called at line {})", class.name(), paren.line);
+    match (error, class) {
+        (LoxError::RuntimeError { token: None, message }, _) => LoxError::RuntimeError {
+            token: Some(paren.clone()),
+            message,
+        },
+        (LoxError::Constructing { error, called_from: previous }, Some(class)) => LoxError::Constructing {
+            error,
+            called_from: previous + &called_from(class),
+        },
+        (error @ (LoxError::RuntimeError { .. } | LoxError::Thrown { .. }), Some(class)) => LoxError::Constructing {
+            error: Box::new(error),
+            called_from: called_from(class),
+        },
+        (error, _) => error,
+    }
+}
+
+// reads `get.name` off an already evaluated object
+fn get_property(object: Literal, get: &GetArgs, interpreter: &mut Interpreter) -> LoxResult<Literal> {
+    match object {
+        Literal::Instance(i) => i.get(get.name.clone(), interpreter),
+        Literal::Module(module) => module.get(&get.name),
+        _ => match object.class_ref() {
+            Some(class) => {
+                class.check_private(&get.name, interpreter)?;
+                class.get(&get.name)
+            }
+            None => Err(LoxError::RuntimeError {
+                token: Some(get.name.clone()),
+                message: "Only instances and classes have properties".to_string(),
+            }),
+        },
+    }
+}
+
+fn set_property(object: Literal, name: &Token, value: Literal, interpreter: &mut Interpreter) -> LoxResult<()> {
+    match object {
+        Literal::Instance(i) => i.set(name.clone(), value, interpreter),
+        Literal::Module(module) => Err(LoxError::RuntimeError {
+            token: Some(name.clone()),
+            message: format!("Cannot assign to {}, module exports are read-only", module.name()),
+        }),
+        _ => match object.class_ref() {
+            Some(class) => {
+                class.check_private(name, interpreter)?;
+                class.set(name, value);
+                Ok(())
+            }
+            None => Err(LoxError::RuntimeError {
+                token: Some(name.clone()),
+                message: "Only instances and classes have fields".to_string(),
+            }),
+        },
+    }
+}
+
 pub fn is_truthy(value: &Literal) -> bool {
     match value {
         Literal::Basic(AtomicLiteral::Nil) => false,
@@ -153,45 +348,285 @@ pub fn is_truthy(value: &Literal) -> bool {
     }
 }
 
+// turns a Lox index into a position in a list of `len` items, rejecting anything out of range
+fn list_position(index: &Literal, len: usize, bracket: &Token) -> LoxResult<usize> {
+    match index {
+        Literal::Basic(AtomicLiteral::Number(i)) => {
+            if *i < 0 || *i as usize >= len {
+                Err(LoxError::RuntimeError {
+                    token: Some(bracket.clone()),
+                    message: format!("Index {} out of range for list of length {}", i, len),
+                })
+            } else {
+                Ok(*i as usize)
+            }
+        }
+        _ => Err(LoxError::RuntimeError {
+            token: Some(bracket.clone()),
+            message: "List index must be an integer".to_string(),
+        }),
+    }
+}
+
+// only plain values can be map keys, integral floats share a key with the matching int since `1 == 1.0`
+pub fn map_key(value: &Literal) -> Option<AtomicLiteral> {
+    match value {
+        Literal::Basic(AtomicLiteral::Float(n))
+            if n.fract() == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64 =>
+        {
+            Some(AtomicLiteral::Number(*n as i32))
+        }
+        Literal::Basic(atom) => Some(atom.clone()),
+        _ => None,
+    }
+}
+
+fn expect_map_key(value: &Literal, bracket: &Token) -> LoxResult<AtomicLiteral> {
+    map_key(value).ok_or_else(|| LoxError::RuntimeError {
+        token: Some(bracket.clone()),
+        message: "Map keys must be strings, numbers, booleans or null".to_string(),
+    })
+}
+
+fn index_get(object: &Literal, position: &Literal, bracket: &Token, interpreter: &mut Interpreter) -> LoxResult<Literal> {
+    match object {
+        Literal::Instance(instance) => call_special(instance, "__index__", vec![position.clone()], bracket, interpreter)
+            .unwrap_or_else(|| Err(LoxError::RuntimeError {
+                token: Some(bracket.clone()),
+                message: format!("{} instances can't be indexed, define __index__ to allow it", instance.class_name()),
+            })),
+        Literal::List(items) => {
+            let items = items.borrow();
+            let i = list_position(position, items.len(), bracket)?;
+            Ok(items[i].clone())
+        }
+        Literal::Map(map) => {
+            let key = expect_map_key(position, bracket)?;
+            map.borrow().get(&key).ok_or_else(|| LoxError::RuntimeError {
+                token: Some(bracket.clone()),
+                message: format!("Undefined key {}", Literal::Basic(key)),
+            })
+        }
+        _ => Err(LoxError::RuntimeError {
+            token: Some(bracket.clone()),
+            message: "Only lists and maps can be indexed".to_string(),
+        }),
+    }
+}
+
+fn index_set(object: &Literal, position: &Literal, value: Literal, bracket: &Token, interpreter: &mut Interpreter) -> LoxResult<()> {
+    match object {
+        Literal::Instance(instance) => {
+            match call_special(instance, "__setindex__", vec![position.clone(), value], bracket, interpreter) {
+                Some(result) => result.map(|_| ()),
+                None => Err(LoxError::RuntimeError {
+                    token: Some(bracket.clone()),
+                    message: format!("{} instances don't support index assignment, define __setindex__ to allow it", instance.class_name()),
+                }),
+            }
+        }
+        Literal::List(items) => {
+            let mut items = items.borrow_mut();
+            let i = list_position(position, items.len(), bracket)?;
+            items[i] = value;
+            Ok(())
+        }
+        Literal::Map(map) => {
+            let key = expect_map_key(position, bracket)?;
+            map.borrow_mut().insert(key, value);
+            Ok(())
+        }
+        _ => Err(LoxError::RuntimeError {
+            token: Some(bracket.clone()),
+            message: "Only lists and maps can be indexed".to_string(),
+        }),
+    }
+}
+
+// reads a variable through the distance the resolver recorded for `expr`, unresolved names are globals
+pub fn lookup_variable(interpreter: &Interpreter, name: &Token, expr: &ExpressionType) -> LoxResult<Literal> {
+    let distance = interpreter.local.borrow().get(expr).copied();
+    match distance {
+        Some(d) => {
+            match interpreter.env.borrow().get_at(d, &name.lexeme) {
+                Ok(value) => Ok(value),
+                Err(_) => interpreter.env.borrow().get(name)
+            }
+        },
+        None => interpreter.global.borrow().get(name),
+    }
+}
+
+pub fn assign_variable(interpreter: &Interpreter, name: &Token, expr: &ExpressionType, value: Literal) -> LoxResult<()> {
+    let distance = interpreter.local.borrow().get(expr).copied();
+    match distance {
+        Some(d) => interpreter.env.borrow_mut().assign_at(d, name.clone(), value),
+        None => interpreter.global.borrow_mut().assign(name.clone(), value),
+    }
+}
+
+fn compound_operator(operator: &TokenType) -> TokenType {
+    match operator {
+        TokenType::PLUSEQUAL | TokenType::INCREMENTOR => TokenType::PLUS,
+        TokenType::MINUSEQUAL | TokenType::DECREMENTOR => TokenType::MINUS,
+        TokenType::STAREQUAL => TokenType::STAR,
+        TokenType::SLASHEQUAL => TokenType::SLASH,
+        _ => TokenType::MODULO,
+    }
+}
+
+// the new value for ++ and --, which unlike += never fall back to string concatenation
+fn step_number(operator: &Token, current: Literal) -> LoxResult<Literal> {
+    match current {
+        Literal::Basic(AtomicLiteral::Number(_)) | Literal::Basic(AtomicLiteral::Float(_)) => {
+            let one = Literal::Basic(AtomicLiteral::Number(1));
+            binary_operation(&compound_operator(&operator.tokentype), operator, current, one)
+        }
+        _ => Err(LoxError::RuntimeError {
+            token: Some(operator.clone()),
+            message: "Increment and decrement operators can only be applied to numbers".to_string(),
+        }),
+    }
+}
+
+// read-modify-write of an assignable expression, the target's object and index are evaluated only once.
+// returns the old and the new value
+fn update_target<F>(target: &ExpressionType, interpreter: &mut Interpreter, compute: F) -> LoxResult<(Literal, Literal)>
+where
+    F: FnOnce(&mut Interpreter, Literal) -> LoxResult<Literal>,
+{
+    match target {
+        ExpressionType::Variable(name) => {
+            let current = lookup_variable(interpreter, name, target)?;
+            let updated = compute(interpreter, current.clone())?;
+            assign_variable(interpreter, name, target, updated.clone())?;
+            Ok((current, updated))
+        }
+        ExpressionType::Get(get) => {
+            let object = get.object.evaluate(interpreter)?;
+            let current = get_property(object.clone(), get, interpreter)?;
+            let updated = compute(interpreter, current.clone())?;
+            set_property(object, &get.name, updated.clone(), interpreter)?;
+            Ok((current, updated))
+        }
+        ExpressionType::Index(index) => {
+            let object = index.object.evaluate(interpreter)?;
+            let position = index.index.evaluate(interpreter)?;
+            let current = index_get(&object, &position, &index.bracket, interpreter)?;
+            let updated = compute(interpreter, current.clone())?;
+            index_set(&object, &position, updated.clone(), &index.bracket, interpreter)?;
+            Ok((current, updated))
+        }
+        _ => Err(LoxError::RuntimeError {
+            token: None,
+            message: "Invalid assignment target".to_string(),
+        }),
+    }
+}
+
+pub fn overflow_error(operator: &Token) -> LoxError {
+    LoxError::RuntimeError {
+        token: Some(operator.clone()),
+        message: "Integer overflow".to_string(),
+    }
+}
+
+// integer arithmetic is always checked so debug and release builds agree on overflow
+fn checked_int(result: Option<i32>, operator: &Token) -> LoxResult<Literal> {
+    match result {
+        Some(n) => Ok(Literal::Basic(AtomicLiteral::Number(n))),
+        None => Err(overflow_error(operator)),
+    }
+}
+
+pub enum Numbers {
+    Ints(i32, i32),
+    Floats(f64, f64),
+}
+
+impl Numbers {
+    fn divisor_is_zero(&self) -> bool {
+        match self {
+            Numbers::Ints(_, b) => *b == 0,
+            Numbers::Floats(_, b) => *b == 0.0,
+        }
+    }
+}
+
+fn as_float(value: &AtomicLiteral) -> Option<f64> {
+    match value {
+        AtomicLiteral::Number(n) => Some(*n as f64),
+        AtomicLiteral::Float(n) => Some(*n),
+        _ => None,
+    }
+}
+
+// two ints stay ints, as soon as either side is a float both sides are promoted to float
+pub fn numeric_operands(left: &Literal, right: &Literal) -> Option<Numbers> {
+    match (left, right) {
+        (
+            Literal::Basic(AtomicLiteral::Number(a)),
+            Literal::Basic(AtomicLiteral::Number(b)),
+        ) => Some(Numbers::Ints(*a, *b)),
+        (Literal::Basic(a), Literal::Basic(b)) => Some(Numbers::Floats(as_float(a)?, as_float(b)?)),
+        _ => None,
+    }
+}
+
 pub fn is_equal(a: &Literal, b: &Literal) -> LoxResult<bool> {
     match (a, b) {
-        (Literal::Basic(a), Literal::Basic(b)) => Ok(a == b),
+        (Literal::Basic(x), Literal::Basic(y)) => match numeric_operands(a, b) {
+            Some(Numbers::Ints(x, y)) => Ok(x == y),
+            Some(Numbers::Floats(x, y)) => Ok(x == y),
+            None => Ok(x == y),
+        },
         (Literal::Basic(AtomicLiteral::Nil), _) => Ok(false),
         (_, Literal::Basic(AtomicLiteral::Nil)) => Ok(false),        
-        _ => Ok(false),
+        _ => Ok(is_same(a, b)),
+    }
+}
+
+// what same(a, b) checks: everything that lives behind a pointer is compared by identity, plain values
+// by value without mixing ints and floats. classes are compared through their shared fields because
+// `this` in a static method wraps the class in a new callable
+pub fn is_same(a: &Literal, b: &Literal) -> bool {
+    match (a, b) {
+        (Literal::Basic(x), Literal::Basic(y)) => x == y,
+        (Literal::Instance(x), Literal::Instance(y)) => x.same(y),
+        (Literal::List(x), Literal::List(y)) => Rc::ptr_eq(x, y),
+        (Literal::Map(x), Literal::Map(y)) => Rc::ptr_eq(x, y),
+        (Literal::Module(x), Literal::Module(y)) => Rc::ptr_eq(x, y),
+        (Literal::Trait(x), Literal::Trait(y)) => Rc::ptr_eq(x, y),
+        (Literal::LoxCallable(x), Literal::LoxCallable(y)) => match (a.class_ref(), b.class_ref()) {
+            (Some(x), Some(y)) => x.same(y),
+            _ => Rc::ptr_eq(x, y),
+        },
+        _ => false,
     }
 }
 
 impl ExpressionType {
 
     pub fn evaluate(&self, interpreter: &mut Interpreter) -> LoxResult<Literal> {
-
-        let varibale_lookup = |name: &Token, expr: &ExpressionType| {
-            let distance = interpreter.local.get(expr);
-            match distance {
-                Some(d) => {
-                    match interpreter.env.borrow().get_at(*d, &name.lexeme) {
-                        Ok(value) => Ok(value),
-                        Err(_) => interpreter.env.borrow().get(&name)
-                    }
-                },
-                None => interpreter.global.borrow().get(&name),
-            }
-        };
-
         match self {
-            ExpressionType::This(this) => varibale_lookup(this,self),
+            ExpressionType::This(this) => lookup_variable(interpreter, this, self),
 
             ExpressionType::Super(sup) =>{
-                let distance = interpreter.local.get(self);
+                let distance = interpreter.local.borrow().get(self).copied();
                 match distance {
                     Some(distance) => {
-                        let superclass = interpreter.env.borrow().get_at(*distance, "super")?.as_class()?;
-                        let object = interpreter.env.borrow().get_at(*distance-1, "this")?.as_instance()?;
-                        let method = superclass.find_method(&sup.method.lexeme);
+                        let superclass = interpreter.env.borrow().get_at(distance, "super")?.as_class(&sup.keyword)?;
+                        let this = interpreter.env.borrow().get_at(distance-1, "this")?;
+                        let method = match &this {
+                            Literal::Instance(object) => superclass
+                                .find_method(&sup.method.lexeme)
+                                .map(|method| Literal::LoxCallable(Rc::new(method.bind(object)))),
+                            // super.name inside a static method, `this` is the class
+                            _ => this.class_ref().and_then(|class| superclass.find_static(&sup.method.lexeme, class)),
+                        };
                         match method {
                             None => Err(LoxError::RuntimeError { token: Some(sup.method.clone()), message: "method not found".to_string()}),
-                            Some(method) => Ok(Literal::LoxCallable(Rc::new(method.bind(&object))))
+                            Some(method) => Ok(method)
                         }
                     }
                     None => {
@@ -204,23 +639,24 @@ impl ExpressionType {
 
             ExpressionType::Grouping(expr) => expr.evaluate(interpreter),
 
-            ExpressionType::Variable(name) => varibale_lookup(name, self),
+            ExpressionType::Variable(name) => lookup_variable(interpreter, name, self),
 
             ExpressionType::Assignment(assignment) => {
                 let value = assignment.value.evaluate(interpreter)?;
-                let distance = interpreter.local.get(self);
-                match distance {
-                    Some(d) => {
-                        interpreter.env.borrow_mut().assign_at(*d, assignment.name.clone(), value.clone())?;
-                    }
-                    None => {
-                        interpreter.global.borrow_mut().assign(assignment.name.clone(), value.clone())?;
-                    }
-                }
+                assign_variable(interpreter, &assignment.name, self, value.clone())?;
                 Ok(value)
             }
 
+            ExpressionType::Conditional(conditional) => {
+                if is_truthy(&conditional.condition.evaluate(interpreter)?) {
+                    conditional.then_branch.evaluate(interpreter)
+                } else {
+                    conditional.else_branch.evaluate(interpreter)
+                }
+            }
+
             ExpressionType::Call(called) => {
+                // for obj?.method() the callee short-circuits first, so the arguments aren't evaluated
                 let callee = called.callee.evaluate(interpreter)?;
                 let mut args: Vec<Literal> = Vec::new();
                 for arg in &called.args {
@@ -235,7 +671,8 @@ impl ExpressionType {
                                 message: format!("Expected {} arguments but got {}", function.arity(), args.len()),
                             });
                         }
-                        function.call(interpreter, args)
+                        let result = function.call(interpreter, args);
+                        result.map_err(|e| call_site_error(e, &called.paren, function.as_any().downcast_ref::<LoxClass>()))
                     }
                     _ => {
                         Err(LoxError::RuntimeError {
@@ -246,87 +683,109 @@ impl ExpressionType {
                 }
             }
 
-            ExpressionType::Postfix(post) => match &*post.expr {
-                ExpressionType::Variable(name) => {
-                    let current = match interpreter.env.borrow().get(&name) {
-                        Ok(val) => val,
-                        Err(e) => return Err(e),
-                    };
-                    
-                    match (&post.operator, &current) {
-                        (TokenType::INCREMENTOR, Literal::Basic(AtomicLiteral::Number(n))) => {
-                            match interpreter.env.borrow_mut().assign(
-                                name.clone(),
-                                Literal::Basic(AtomicLiteral::Number(n + 1)),
-                            ) {
-                                Ok(()) => Ok(Literal::Basic(AtomicLiteral::Number(*n))),
-                                Err(e) => Err(e),
-                            }
-                        }
-                        (TokenType::DECREMENTOR, Literal::Basic(AtomicLiteral::Number(n))) => {
-                            match interpreter.env.borrow_mut().assign(
-                                name.clone(),
-                                Literal::Basic(AtomicLiteral::Number(n - 1)),
-                            ) {
-                                Ok(()) => Ok(Literal::Basic(AtomicLiteral::Number(*n))),
-                                Err(e) => Err(e),
-                            }
-                        }
-                        _ => Err(LoxError::RuntimeError {
-                            token: Some(name.clone()),
-                            message: "Postfix operators can only be applied to numbers".to_string(),
-                        })
-                    }
+            ExpressionType::Postfix(post) => {
+                let (previous, _) = update_target(&post.expr, interpreter, |_, current| {
+                    step_number(&post.operator, current)
+                })?;
+                Ok(previous)
+            }
+
+            ExpressionType::List(list) => {
+                let mut items = Vec::new();
+                for element in &list.elements {
+                    items.push(element.evaluate(interpreter)?);
                 }
-                _ => Err(LoxError::RuntimeError {
-                        token: None,
-                        message: "Postfix operators can only be applied to variables".to_string(),
-                    }),
-            },
+                Ok(Literal::List(Rc::new(RefCell::new(items))))
+            }
+
+            ExpressionType::Lambda(lambda) => {
+                let function = LoxFunction::new(Rc::new(&lambda.function), interpreter, false);
+                Ok(Literal::LoxCallable(Rc::new(function)))
+            }
+
+            ExpressionType::Map(map) => {
+                let mut entries = LoxMap::new();
+                for (key, value) in &map.entries {
+                    let key = expect_map_key(&key.evaluate(interpreter)?, &map.brace)?;
+                    let value = value.evaluate(interpreter)?;
+                    entries.insert(key, value);
+                }
+                Ok(Literal::Map(Rc::new(RefCell::new(entries))))
+            }
+
+            ExpressionType::Index(index) => {
+                let object = index.object.evaluate(interpreter)?;
+                let position = index.index.evaluate(interpreter)?;
+                index_get(&object, &position, &index.bracket, interpreter)
+            }
+
+            ExpressionType::IndexSet(index) => {
+                let object = index.object.evaluate(interpreter)?;
+                let position = index.index.evaluate(interpreter)?;
+                let value = index.value.evaluate(interpreter)?;
+                index_set(&object, &position, value.clone(), &index.bracket, interpreter)?;
+                Ok(value)
+            }
+
+            ExpressionType::CompoundAssign(compound) => {
+                let kind = compound_operator(&compound.operator.tokentype);
+                let (_, updated) = update_target(&compound.target, interpreter, |interpreter, current| {
+                    if let TokenType::INCREMENTOR | TokenType::DECREMENTOR = compound.operator.tokentype {
+                        return step_number(&compound.operator, current);
+                    }
+                    let value = compound.value.evaluate(interpreter)?;
+                    apply_operator(&kind, &compound.operator, current, value, interpreter)
+                })?;
+                Ok(updated)
+            }
 
             ExpressionType::Get(get) => {
                 let object = get.object.evaluate(interpreter)?;
                 match object {
-                    Literal::Instance(i) => {
-                        let result = i.get(get.name.clone())?;
-                        Ok(result)
-                    }
-                    _ => {
-                        return Err(LoxError::RuntimeError {
-                            token: Some(get.name.clone()),
-                            message: "Only instances have properties".to_string(),
-                        })
-                    }
+                    Literal::Basic(AtomicLiteral::Nil) if get.optional => Err(LoxError::ShortCircuit),
+                    _ => get_property(object, get, interpreter),
                 }
             }
 
+            ExpressionType::OptionalChain(chain) => match chain.evaluate(interpreter) {
+                Err(LoxError::ShortCircuit) => Ok(Literal::Basic(AtomicLiteral::Nil)),
+                result => result,
+            },
+
             ExpressionType::Set(set) => {
+                let object = set.object.evaluate(interpreter)?;
+                let value = set.value.evaluate(interpreter)?;
+                set_property(object, &set.name, value.clone(), interpreter)?;
+                Ok(value)
+            }
+
+            ExpressionType::FieldDefault(set) => {
                 let object = set.object.evaluate(interpreter)?;
                 let value = set.value.evaluate(interpreter)?;
                 match object {
-                    Literal::Instance(i) => {
-                        i.set(set.name.clone(), value.clone());
+                    Literal::Instance(instance) => {
+                        instance.set_default(&set.name, value.clone(), interpreter)?;
                         Ok(value)
                     }
-                    _ => {
-                        Err(LoxError::RuntimeError {
-                            token: Some(set.name.clone()),
-                            message: "Only instances have fields".to_string(),
-                        })
-                    }
+                    _ => Err(LoxError::RuntimeError {
+                        token: Some(set.name.clone()),
+                        message: "Only instances have fields".to_string(),
+                    }),
                 }
             }
 
             ExpressionType::Unary(expr) => {
                 let right = &expr.right.evaluate(interpreter)?;
                 
-                match expr.operator {
+                match expr.operator.tokentype {
                     TokenType::MINUS => {
                         if let Literal::Basic(AtomicLiteral::Number(n)) = right {
-                            Ok(Literal::Basic(AtomicLiteral::Number(-n)))
+                            checked_int(n.checked_neg(), &expr.operator)
+                        } else if let Literal::Basic(AtomicLiteral::Float(n)) = right {
+                            Ok(Literal::Basic(AtomicLiteral::Float(-n)))
                         } else {
                             Err(LoxError::RuntimeError {
-                                token: None,
+                                token: Some(expr.operator.clone()),
                                 message: "Operand must be a number".to_string(),
                             })
                         }
@@ -340,7 +799,13 @@ impl ExpressionType {
 
             ExpressionType::Logical(expr) => {
                 let left = &expr.left.evaluate(interpreter)?;
-                if expr.operator == TokenType::OR {
+                if expr.operator.tokentype == TokenType::QUESTIONQUESTION {
+                    // unlike `or`, only nil falls through, so false and 0 are kept
+                    match left {
+                        Literal::Basic(AtomicLiteral::Nil) => expr.right.evaluate(interpreter),
+                        _ => Ok(left.clone()),
+                    }
+                } else if expr.operator.tokentype == TokenType::OR {
                     if is_truthy(&left) {
                         Ok(left.clone())
                     } else {
@@ -358,156 +823,192 @@ impl ExpressionType {
             ExpressionType::Binary(expr) => {
                 let left = expr.left.evaluate(interpreter)?;
                 let right = expr.right.evaluate(interpreter)?;
+                apply_operator(&expr.operator.tokentype, &expr.operator, left, right, interpreter)
+            }
+        }
+    }
+}
 
-                match expr.operator {
-                    TokenType::PLUS => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Number(a + b))),
-                        (
-                            Literal::Basic(AtomicLiteral::String(a)),
-                            Literal::Basic(AtomicLiteral::String(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::String(a.to_string() + &b))),
-                        (
-                            Literal::Basic(AtomicLiteral::String(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::String(a.to_owned() + &b.to_string()))),
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::String(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::String(a.to_string() + &b))),
-                        _ => return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be two numbers or two strings".to_string(),
-                            }),
-                    },
-
-                    TokenType::MODULO => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => {
-                            if b == 0 {
-                                return Err(LoxError::RuntimeError {
-                                    token: None,
-                                    message: "Modulo by zero".to_string(),
-                                });
-                            }
-                            Ok(Literal::Basic(AtomicLiteral::Number(a % b)))
-                        },
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        }
-                    },
-
-                    TokenType::MINUS => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Number(a - b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-
-                    TokenType::STAR => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Number(a * b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-
-                    TokenType::SLASH => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(_)),
-                            Literal::Basic(AtomicLiteral::Number(0)),
-                        ) => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Division by zero".to_string(),
-                            });
-                        }
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Number(a / b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-                    TokenType::GREATER => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Bool(a > b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-                    TokenType::GREATEREQUAL => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Bool(a >= b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-                    TokenType::LESS => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Bool(a < b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-                    TokenType::LESSEQUAL => match (left, right) {
-                        (
-                            Literal::Basic(AtomicLiteral::Number(a)),
-                            Literal::Basic(AtomicLiteral::Number(b)),
-                        ) => Ok(Literal::Basic(AtomicLiteral::Bool(a <= b))),
-                        _ => {
-                            return Err(LoxError::RuntimeError {
-                                token: None,
-                                message: "Operands must be numbers".to_string(),
-                            });
-                        },
-                    },
-                    TokenType::EQUALEQUAL => Ok(Literal::Basic(AtomicLiteral::Bool(
-                        is_equal(&left, &right)?,
-                    ))),
-                    TokenType::BANGEQUAL => Ok(Literal::Basic(AtomicLiteral::Bool(
-                        !is_equal(&left, &right)?,
-                    ))),
-                    _ => Ok(Literal::Basic(AtomicLiteral::Nil)), // should not reach here
-                }
+// the method a class defines to overload an operator, `a + b` becomes a.__add__(b)
+fn special_method(kind: &TokenType) -> Option<&'static str> {
+    match kind {
+        TokenType::PLUS => Some("__add__"),
+        TokenType::MINUS => Some("__sub__"),
+        TokenType::STAR => Some("__mul__"),
+        TokenType::SLASH => Some("__div__"),
+        TokenType::MODULO => Some("__mod__"),
+        TokenType::LESS => Some("__lt__"),
+        TokenType::LESSEQUAL => Some("__le__"),
+        TokenType::GREATER => Some("__gt__"),
+        TokenType::GREATEREQUAL => Some("__ge__"),
+        TokenType::EQUALEQUAL | TokenType::BANGEQUAL => Some("__eq__"),
+        _ => None,
+    }
+}
+
+// None when the instance's class doesn't define `name`
+fn call_special(instance: &LoxInstance, name: &str, args: Vec<Literal>, token: &Token, interpreter: &mut Interpreter) -> Option<LoxResult<Literal>> {
+    let method = instance.bound_method(name)?;
+    if method.arity() != args.len() {
+        return Some(Err(LoxError::RuntimeError {
+            token: Some(token.clone()),
+            message: format!("{} must take {} argument(s) but takes {}", name, args.len(), method.arity()),
+        }));
+    }
+    Some(method.call(interpreter, args))
+}
+
+// binary_operation plus operator overloading, an instance on the left hands the operator to its special method.
+// != is the negation of __eq__
+pub fn apply_operator(kind: &TokenType, operator: &Token, left: Literal, right: Literal, interpreter: &mut Interpreter) -> LoxResult<Literal> {
+    let overload = match (&left, special_method(kind)) {
+        (Literal::Instance(instance), Some(name)) => call_special(instance, name, vec![right.clone()], operator, interpreter),
+        _ => None,
+    };
+    if let Some(result) = overload {
+        return match kind {
+            TokenType::BANGEQUAL => Ok(Literal::Basic(AtomicLiteral::Bool(!is_truthy(&result?)))),
+            _ => result,
+        };
+    }
+
+    // concatenating a string with an object goes through toString(), plain values are left to binary_operation
+    if *kind == TokenType::PLUS {
+        match (&left, &right) {
+            (Literal::Basic(AtomicLiteral::String(a)), other) if !matches!(other, Literal::Basic(_)) => {
+                return Ok(Literal::Basic(AtomicLiteral::String(a.clone() + &interpreter.stringify(other)?)));
+            }
+            (other, Literal::Basic(AtomicLiteral::String(b))) if !matches!(other, Literal::Basic(_)) => {
+                return Ok(Literal::Basic(AtomicLiteral::String(interpreter.stringify(other)? + b)));
+            }
+            _ => {}
+        }
+    }
+
+    let message = match (&left, &right, special_method(kind)) {
+        (Literal::Instance(instance), _, Some(name)) => Some(format!(
+            "{} instances don't support '{}', define {} to overload it",
+            instance.class_name(), kind, name
+        )),
+        (_, Literal::Instance(instance), Some(_)) => Some(format!(
+            "'{}' with a {} instance on the right isn't supported, overloaded operators need the instance on the left",
+            kind, instance.class_name()
+        )),
+        _ => None,
+    };
+    match (binary_operation(kind, operator, left, right), message) {
+        (Err(LoxError::RuntimeError { token, .. }), Some(message)) => Err(LoxError::RuntimeError { token, message }),
+        (result, _) => result,
+    }
+}
+
+// `kind` picks the arithmetic, `operator` is the token errors point at, they only differ for compound assignment
+pub fn binary_operation(kind: &TokenType, operator: &Token, left: Literal, right: Literal) -> LoxResult<Literal> {
+    match kind {
+        // as soon as one side is a string the other side is stringified, which is also what interpolation relies on
+        TokenType::PLUS => match (left, right) {
+            (Literal::Basic(AtomicLiteral::String(a)), right) => {
+                Ok(Literal::Basic(AtomicLiteral::String(a + &right.to_string())))
+            }
+            (left, Literal::Basic(AtomicLiteral::String(b))) => {
+                Ok(Literal::Basic(AtomicLiteral::String(left.to_string() + &b)))
             }
+            (left, right) => match numeric_operands(&left, &right) {
+                Some(Numbers::Ints(a, b)) => checked_int(a.checked_add(b), operator),
+                Some(Numbers::Floats(a, b)) => Ok(Literal::Basic(AtomicLiteral::Float(a + b))),
+                None => Err(LoxError::RuntimeError {
+                    token: Some(operator.clone()),
+                    message: "Operands must be two numbers, or one of them a string".to_string(),
+                }),
+            },
+        },
+
+        TokenType::MODULO => match numeric_operands(&left, &right) {
+            Some(numbers) if numbers.divisor_is_zero() => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Modulo by zero".to_string(),
+            }),
+            Some(Numbers::Ints(a, b)) => checked_int(a.checked_rem(b), operator),
+            Some(Numbers::Floats(a, b)) => Ok(Literal::Basic(AtomicLiteral::Float(a % b))),
+            None => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Operands must be numbers".to_string(),
+            }),
+        },
+
+        TokenType::MINUS => match numeric_operands(&left, &right) {
+            Some(Numbers::Ints(a, b)) => checked_int(a.checked_sub(b), operator),
+            Some(Numbers::Floats(a, b)) => Ok(Literal::Basic(AtomicLiteral::Float(a - b))),
+            None => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Operands must be numbers".to_string(),
+            }),
+        },
+
+        TokenType::STAR => match numeric_operands(&left, &right) {
+            Some(Numbers::Ints(a, b)) => checked_int(a.checked_mul(b), operator),
+            Some(Numbers::Floats(a, b)) => Ok(Literal::Basic(AtomicLiteral::Float(a * b))),
+            None => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Operands must be numbers".to_string(),
+            }),
+        },
+
+        // int / int truncates towards zero, any float operand gives a float quotient
+        TokenType::SLASH => match numeric_operands(&left, &right) {
+            Some(numbers) if numbers.divisor_is_zero() => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Division by zero".to_string(),
+            }),
+            Some(Numbers::Ints(a, b)) => checked_int(a.checked_div(b), operator),
+            Some(Numbers::Floats(a, b)) => Ok(Literal::Basic(AtomicLiteral::Float(a / b))),
+            None => Err(LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: "Operands must be numbers".to_string(),
+            }),
+        },
+        TokenType::GREATER | TokenType::GREATEREQUAL | TokenType::LESS | TokenType::LESSEQUAL => {
+            let ordering = match numeric_operands(&left, &right) {
+                Some(Numbers::Ints(a, b)) => a.partial_cmp(&b),
+                Some(Numbers::Floats(a, b)) => a.partial_cmp(&b),
+                None => {
+                    return Err(LoxError::RuntimeError {
+                        token: Some(operator.clone()),
+                        message: "Operands must be numbers".to_string(),
+                    });
+                }
+            };
+            // NaN compares false against everything
+            let result = match ordering {
+                Some(ordering) => match kind {
+                    TokenType::GREATER => ordering.is_gt(),
+                    TokenType::GREATEREQUAL => ordering.is_ge(),
+                    TokenType::LESS => ordering.is_lt(),
+                    _ => ordering.is_le(),
+                },
+                None => false,
+            };
+            Ok(Literal::Basic(AtomicLiteral::Bool(result)))
         }
+        // obj is Class, true for instances of the class and of its subclasses
+        TokenType::IS => {
+            let class = right.class_ref().ok_or_else(|| LoxError::RuntimeError {
+                token: Some(operator.clone()),
+                message: format!("Right operand of 'is' must be a class, got {}", right),
+            })?;
+            let result = match &left {
+                Literal::Instance(instance) => instance.class().is_subclass_of(class),
+                _ => false,
+            };
+            Ok(Literal::Basic(AtomicLiteral::Bool(result)))
+        }
+
+        TokenType::EQUALEQUAL => Ok(Literal::Basic(AtomicLiteral::Bool(
+            is_equal(&left, &right)?,
+        ))),
+        TokenType::BANGEQUAL => Ok(Literal::Basic(AtomicLiteral::Bool(
+            !is_equal(&left, &right)?,
+        ))),
+        _ => Ok(Literal::Basic(AtomicLiteral::Nil)), // should not reach here
     }
-}
\ No newline at end of file
+}
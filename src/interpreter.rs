@@ -1,33 +1,166 @@
 use crate::statement::{StatementType};
 use crate::expression::{ExpressionType};
 use crate::token::{Literal,Token};
+use crate::builtins::{ClassName, Fields, GetField, HasField, HasKey, Keys, Len, Methods, Same, SetField, SuperclassOf, Type, Values};
+use crate::callable::{Callable, Native};
 use crate::{clock::Clock, environment::Environment};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
-use crate::lox_error::{LoxResult};
+use crate::lox_class::{ClassMembers, LoxClass};
+use crate::lox_error::{LoxError, LoxResult};
+use crate::lox_instance::LoxInstance;
+use crate::lox_module::LoxModule;
+use std::path::PathBuf;
+use crate::token::{AtomicLiteral, TokenType};
 
 pub struct Interpreter {
     pub global: Rc<RefCell<Environment>>,
     pub env: Rc<RefCell<Environment>>,
-    pub local: HashMap<ExpressionType, usize>,
+    // resolver distances of the module being run, functions carry their own module's map along
+    pub local: Rc<RefCell<HashMap<ExpressionType, usize>>>,
+    // the file being run, imports are resolved relative to it. None for code typed into the prompt
+    pub current_file: Option<PathBuf>,
+    // every module that finished loading, keyed by canonical path so each file runs once
+    pub modules: HashMap<PathBuf, Rc<LoxModule>>,
+    // the chain of modules currently being loaded, used to report import cycles
+    pub loading: Vec<PathBuf>,
+    // resolver warnings from imported modules, the entry file's are printed before it runs
+    pub warnings: Vec<String>,
+    // the declaration of the class whose code is running, None outside of classes
+    pub current_class: Option<Rc<Token>>,
+    // the class of the values that catch blocks receive, it is not reachable by name from scripts
+    error_class: Rc<LoxClass>,
 }
 
 impl Interpreter {
-    pub fn new() -> Self {
-        let globals = Rc::new(RefCell::new(Environment::new(None)));
-        let _ = globals.borrow_mut().define(
-            Token::new(crate::token::TokenType::IDENTIFIER, "clock".to_string(),0,crate::token::AtomicLiteral::Nil),
-            Literal::LoxCallable(Rc::new(Clock)),
-        );
-    
+    pub fn new(current_file: Option<PathBuf>) -> Self {
+        let globals = Self::global_environment();
+        // the entry file counts as loading too, so importing it back is reported as a cycle
+        let loading = current_file.iter().filter_map(|file| file.canonicalize().ok()).collect();
         Interpreter {
             global: globals.clone(),
             env: globals,
-            local: HashMap::new(),
+            local: Rc::new(RefCell::new(HashMap::new())),
+            current_file,
+            modules: HashMap::new(),
+            loading,
+            warnings: Vec::new(),
+            current_class: None,
+            error_class: Rc::new(LoxClass::new(
+                Rc::new(Token::new(TokenType::IDENTIFIER, "Error".to_string(), 0, AtomicLiteral::Nil)),
+                ClassMembers::default(),
+                None,
+            )),
         }
     }
+
+    // a fresh global scope holding the native functions, each module starts from one of these
+    pub fn global_environment() -> Rc<RefCell<Environment>> {
+        let globals = Rc::new(RefCell::new(Environment::new(None)));
+        let natives: Vec<(&'static str, Rc<dyn Callable>)> = vec![
+            ("clock", Rc::new(Clock)),
+            ("len", Rc::new(Len)),
+            ("keys", Rc::new(Keys)),
+            ("values", Rc::new(Values)),
+            ("hasKey", Rc::new(HasKey)),
+            ("same", Rc::new(Same)),
+            ("type", Rc::new(Type)),
+            ("fields", Rc::new(Fields)),
+            ("hasField", Rc::new(HasField)),
+            ("methods", Rc::new(Methods)),
+            ("className", Rc::new(ClassName)),
+            ("getField", Rc::new(GetField)),
+            ("setField", Rc::new(SetField)),
+            ("superclassOf", Rc::new(SuperclassOf)),
+        ];
+        for (name, native) in natives {
+            let _ = globals.borrow_mut().define(
+                Token::new(crate::token::TokenType::IDENTIFIER, name.to_string(),0,crate::token::AtomicLiteral::Nil),
+                Literal::LoxCallable(Rc::new(Native::new(name, native))),
+            );
+        }
+        globals
+    }
+
+    // turns a runtime error or a thrown value into what a catch block binds. thrown instances are
+    // passed through untouched, everything else becomes an Error instance with message, line and value.
+    // the constructor call sites are only for uncaught errors, the catch block sees the error as raised
+    pub fn error_value(&self, error: LoxError) -> Literal {
+        let (message, line, value) = match error {
+            LoxError::Constructing { error, .. } => return self.error_value(*error),
+            LoxError::Thrown { value: Literal::Instance(instance), .. } => return Literal::Instance(instance),
+            LoxError::Thrown { line, value, .. } => (value.to_string(), Some(line), value),
+            LoxError::RuntimeError { token, message } => {
+                let value = Literal::Basic(AtomicLiteral::String(message.clone()));
+                (message, token.map(|token| token.line), value)
+            }
+            other => (other.to_string(), None, Literal::Basic(AtomicLiteral::Nil)),
+        };
+        let line = match line {
+            Some(line) => Literal::Basic(AtomicLiteral::Number(line as i32)),
+            None => Literal::Basic(AtomicLiteral::Nil),
+        };
+
+        let instance = LoxInstance::new(self.error_class.clone());
+        instance.set_field("message", Literal::Basic(AtomicLiteral::String(message)));
+        instance.set_field("line", line);
+        instance.set_field("value", value);
+        Literal::Instance(instance)
+    }
  
+    // Display plus the toString() protocol, which needs the interpreter to run the method.
+    // lists and maps are walked so the instances inside them get the same treatment
+    pub fn stringify(&mut self, value: &Literal) -> LoxResult<String> {
+        self.stringify_nested(value, &mut Vec::new())
+    }
+
+    // `printing` holds the lists and maps currently being printed, one that contains itself shows up as [...] or {...}
+    fn stringify_nested(&mut self, value: &Literal, printing: &mut Vec<*const ()>) -> LoxResult<String> {
+        match value {
+            Literal::Instance(instance) => match instance.bound_method("toString") {
+                Some(method) if method.arity() == 0 => match method.call(self, Vec::new())? {
+                    Literal::Basic(AtomicLiteral::String(s)) => Ok(s),
+                    other => Err(LoxError::RuntimeError {
+                        token: None,
+                        message: format!("{}.toString() must return a string, got {}", instance.class_name(), other),
+                    }),
+                },
+                _ => Ok(value.to_string()),
+            },
+            Literal::List(items) => {
+                let id = Rc::as_ptr(items) as *const ();
+                if printing.contains(&id) {
+                    return Ok("[...]".to_string());
+                }
+                // a toString() could change the list, so it is not kept borrowed while running Lox code
+                let items = items.borrow().clone();
+                printing.push(id);
+                let mut parts = Vec::new();
+                for item in &items {
+                    parts.push(self.stringify_nested(item, printing)?);
+                }
+                printing.pop();
+                Ok(format!("[{}]", parts.join(", ")))
+            }
+            Literal::Map(map) => {
+                let id = Rc::as_ptr(map) as *const ();
+                if printing.contains(&id) {
+                    return Ok("{...}".to_string());
+                }
+                let entries = map.borrow().entries().to_vec();
+                printing.push(id);
+                let mut parts = Vec::new();
+                for (key, item) in &entries {
+                    parts.push(format!("{}: {}", Literal::Basic(key.clone()), self.stringify_nested(item, printing)?));
+                }
+                printing.pop();
+                Ok(format!("{{{}}}", parts.join(", ")))
+            }
+            _ => Ok(value.to_string()),
+        }
+    }
+
     pub fn resolve(&mut self, expr: &ExpressionType, depth: usize) {
-        self.local.insert(expr.clone(), depth);
+        self.local.borrow_mut().insert(expr.clone(), depth);
     }
 
     pub fn interpreter(&mut self, statements: &[StatementType]) -> LoxResult<()> {
@@ -1,28 +1,184 @@
+use crate::lox_instance::LoxInstance;
 use crate::loxfuncs::LoxFunction;
 use crate::{interpreter::Interpreter, token::Token};
 use crate::token::Literal;
 use crate::callable::Callable;
-use crate::lox_error::LoxResult;
-use std::collections::HashMap;
+use crate::lox_error::{LoxError, LoxResult};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::rc::Rc;
+
+type FunctionTable = HashMap<String, LoxFunction>;
+
+// the functions a class body declares, each table keyed by name
+#[derive(Clone, Default)]
+pub struct ClassMembers {
+    pub methods: FunctionTable,
+    pub static_methods: FunctionTable,
+    pub getters: FunctionTable,
+    pub setters: FunctionTable,
+    // declared without a body here or in an included trait
+    pub abstract_methods: BTreeSet<String>,
+    // assigns the defaults of the `let` fields in the class body
+    pub field_initializer: Option<LoxFunction>,
+    // the `#name` members only code from this class body may use
+    pub private_names: HashSet<String>,
+}
+
 #[derive(Clone)]
 pub struct LoxClass {
-    name: Token,
-    methods: HashMap<String, LoxFunction>,
+    // shared by every clone, private access is checked against this declaration
+    name: Rc<Token>,
+    members: ClassMembers,
+    // class-level fields, every clone of the class shares them
+    fields: Rc<RefCell<HashMap<String, Literal>>>,
     superclass: Option<Rc<LoxClass>>
 }
 
 impl LoxClass {
-    pub fn new(name: Token, methods: HashMap<String, LoxFunction>, superclass: Option<Rc<LoxClass>>) -> Self {
-        LoxClass { name , methods, superclass}
+    pub fn new(name: Rc<Token>, members: ClassMembers, superclass: Option<Rc<LoxClass>>) -> Self {
+        LoxClass { name , members, fields: Rc::new(RefCell::new(HashMap::new())), superclass}
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name.lexeme
+    }
+
+    pub fn same(&self, other: &LoxClass) -> bool {
+        Rc::ptr_eq(&self.fields, &other.fields)
+    }
+
+    pub fn members(&self) -> &ClassMembers {
+        &self.members
+    }
+
+    // identifies the class declaration, instances key their private fields by it
+    pub fn declaration(&self) -> *const Token {
+        Rc::as_ptr(&self.name)
+    }
+
+    pub fn superclass(&self) -> Option<&Rc<LoxClass>> {
+        self.superclass.as_ref()
+    }
+
+    // true for the class itself too
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        let mut class = Some(self);
+        while let Some(current) = class {
+            if current.same(other) {
+                return true;
+            }
+            class = current.superclass.as_deref();
+        }
+        false
+    }
+
+    // a `#name` is only reachable while code of the class in the chain that declares it is running
+    pub fn check_private(&self, name: &Token, interpreter: &Interpreter) -> LoxResult<()> {
+        if name.lexeme.starts_with('#') {
+            self.private_owner(name, interpreter)?;
+        }
+        Ok(())
+    }
+
+    // the class in the chain whose code is running and that declares `name`. a parent and a subclass
+    // can both declare the same `#name`, each of them only ever sees its own
+    pub fn private_owner(&self, name: &Token, interpreter: &Interpreter) -> LoxResult<&LoxClass> {
+        let mut class = Some(self);
+        while let Some(current) = class {
+            let running = interpreter.current_class.as_ref().is_some_and(|running| Rc::ptr_eq(&current.name, running));
+            if running && current.members.private_names.contains(&name.lexeme) {
+                return Ok(current);
+            }
+            class = current.superclass.as_deref();
+        }
+        Err(LoxError::RuntimeError {
+            token: Some(name.clone()),
+            message: format!("Cannot access private member {} of {} from outside its class", name.lexeme, self.name.lexeme),
+        })
+    }
+
+    // ClassName.member, fields and static methods of a class are found before the superclass's
+    pub fn get(&self, name: &Token) -> LoxResult<Literal> {
+        match self.find_static(&name.lexeme, self) {
+            Some(value) => Ok(value),
+            None => Err(LoxError::RuntimeError {
+                token: Some(name.clone()),
+                message: "Undefined property".to_string(),
+            }),
+        }
+    }
+
+    // assigning through a subclass gives the subclass its own field instead of changing the parent's
+    pub fn set(&self, name: &Token, value: Literal) {
+        self.fields.borrow_mut().insert(name.lexeme.clone(), value);
+    }
+
+    // static methods are bound to `receiver`, the class the member was looked up on
+    pub fn find_static(&self, name: &str, receiver: &LoxClass) -> Option<Literal> {
+        if let Some(value) = self.fields.borrow().get(name) {
+            return Some(value.clone());
+        }
+        if let Some(method) = self.members.static_methods.get(name) {
+            return Some(Literal::LoxCallable(Rc::new(method.bind_class(receiver))));
+        }
+        match &self.superclass {
+            Some(superclass) => superclass.find_static(name, receiver),
+            None => None,
+        }
     }
 
     pub fn find_method (&self, token: &str) -> Option<LoxFunction> {
-        match self.methods.get(token) {
+        self.find_member(token, |members| &members.methods)
+    }
+
+    pub fn find_getter(&self, name: &str) -> Option<LoxFunction> {
+        self.find_member(name, |members| &members.getters)
+    }
+
+    pub fn find_setter(&self, name: &str) -> Option<LoxFunction> {
+        self.find_member(name, |members| &members.setters)
+    }
+
+    // the public instance methods a class has, inherited ones included, sorted and without duplicates
+    pub fn method_names(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        let mut class = Some(self);
+        while let Some(current) = class {
+            names.extend(current.members.methods.keys().filter(|name| !name.starts_with('#')).cloned());
+            class = current.superclass.as_deref();
+        }
+        names.into_iter().collect()
+    }
+
+    // parents set their defaults first so a subclass can override them
+    fn initialize_fields(&self, instance: &LoxInstance, interpreter: &mut Interpreter) -> LoxResult<()> {
+        if let Some(superclass) = &self.superclass {
+            superclass.initialize_fields(instance, interpreter)?;
+        }
+        if let Some(initializer) = &self.members.field_initializer {
+            initializer.bind(instance).call(interpreter, Vec::new())?;
+        }
+        Ok(())
+    }
+
+    // abstract methods from anywhere in the chain that no class in it defines
+    pub fn missing_methods(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        let mut class = Some(self);
+        while let Some(current) = class {
+            names.extend(current.members.abstract_methods.iter().cloned());
+            class = current.superclass.as_deref();
+        }
+        names.into_iter().filter(|name| self.find_method(name).is_none()).collect()
+    }
+
+    fn find_member(&self, name: &str, table: fn(&ClassMembers) -> &FunctionTable) -> Option<LoxFunction> {
+        match table(&self.members).get(name) {
             Some(v) => Some(v.clone()),
             None => {
                 if let Some(superclass) = &self.superclass {
-                    superclass.find_method(token)
+                    superclass.find_member(name, table)
                 } else {
                     None
                 }
@@ -41,9 +197,17 @@ impl Callable for LoxClass {
     }
 
     fn call (&self, interpreter: &mut Interpreter, v:Vec<Literal>) -> LoxResult<Literal> {
-        let instance = crate::lox_instance::LoxInstance::new(self.clone());
+        let missing = self.missing_methods();
+        if !missing.is_empty() {
+            return Err(LoxError::RuntimeError {
+                token: None,
+                message: format!("Cannot instantiate abstract class {}, missing: {}", self.name.lexeme, missing.join(", ")),
+            });
+        }
+        let instance = LoxInstance::new(Rc::new(self.clone()));
+        self.initialize_fields(&instance, interpreter)?;
         if let Some(initializer) = self.find_method("init") {
-            let _ = initializer.bind(&instance).call(interpreter, v);
+            initializer.bind(&instance).call(interpreter, v)?;
         }
         Ok(Literal::Instance(instance))
     }
@@ -51,4 +215,8 @@ impl Callable for LoxClass {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn describe(&self) -> String {
+        format!("<class {}>", self.name.lexeme)
+    }
 }
\ No newline at end of file
@@ -5,7 +5,15 @@ pub enum LoxError {
     ParseError { token: Token, message: String },
     RuntimeError { token: Option<Token>, message: String },
     GeneralError { message: String },
-    ReturnValue(Literal)
+    ReturnValue(Literal),
+    Thrown { line: u32, value: Literal },
+    // a runtime error or thrown value that escaped constructors, called_from collects where they were called.
+    // it is only shown when uncaught, a catch block gets the error inside
+    Constructing { error: Box<LoxError>, called_from: String },
+    Break,
+    Continue,
+    // a `?.` met nil, the enclosing optional chain turns this into nil
+    ShortCircuit,
 }
 
 impl std::fmt::Display for LoxError {
@@ -22,6 +30,10 @@ impl std::fmt::Display for LoxError {
                 }
             }
             LoxError::ReturnValue(_) => write!(f, "ReturnValue error" ), // should not reach this 
+            LoxError::Thrown { line, value } => write!(f, "[Line {}] Uncaught exception: {}", line, value),
+            LoxError::Constructing { error, called_from } => write!(f, "{}{}", error, called_from),
+            LoxError::Break | LoxError::Continue => write!(f, "Loop control error"), // the resolver keeps these inside loops
+            LoxError::ShortCircuit => write!(f, "Optional chain error"), // always caught by the chain it started in
             LoxError::GeneralError { message } => write!(f, "{}", message),
         }
     }
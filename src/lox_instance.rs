@@ -3,32 +3,41 @@ use std::{collections::HashMap, rc::Rc, cell::RefCell};
 use crate::token::Literal;
 use crate::lox_error::{LoxResult,LoxError};
 use crate::token::Token;
+use crate::callable::Callable;
+use crate::interpreter::Interpreter;
+use crate::loxfuncs::LoxFunction;
+
+type PrivateKey = (*const Token, String);
 
 #[derive(Clone)]
 pub struct LoxInstance {
-    class: LoxClass,
-    fields: Rc<RefCell<HashMap<String, Literal>>>
+    class: Rc<LoxClass>,
+    fields: Rc<RefCell<HashMap<String, Literal>>>,
+    // `#name` fields, keyed by the declaration of the class that owns them
+    private_fields: Rc<RefCell<HashMap<PrivateKey, Literal>>>,
 }
 
 impl LoxInstance {
-    pub fn new (class: LoxClass) -> Self {
+    pub fn new (class: Rc<LoxClass>) -> Self {
         LoxInstance { 
             class,
             fields: Rc::new(RefCell::new(HashMap::new())),
+            private_fields: Rc::new(RefCell::new(HashMap::new())),
         }
     }
 
-    pub fn get(&self, name:Token) -> LoxResult<Literal> {
-        if self.fields.borrow().contains_key(&name.lexeme) {
-            match self.fields.borrow().get(&name.lexeme) {
-                Some(value) => return Ok(value.clone()),
-                None => return Err(LoxError::RuntimeError {
-                    token: Some(name),
-                    message: "Undefined property".to_string(),
-                    //should never reach this case because of the contains_key
-                })
-            }   
-        } 
+    // a getter wins over a field of the same name, methods come last
+    pub fn get(&self, name:Token, interpreter: &mut Interpreter) -> LoxResult<Literal> {
+        if name.lexeme.starts_with('#') {
+            return self.get_private(name, interpreter);
+        }
+        if let Some(getter) = self.class.find_getter(&name.lexeme) {
+            return getter.bind(self).call(interpreter, Vec::new());
+        }
+
+        if let Some(value) = self.fields.borrow().get(&name.lexeme) {
+            return Ok(value.clone());
+        }
 
         if let Some(method) = self.class.find_method(&name.lexeme) {
             return Ok(Literal::LoxCallable(Rc::new(method.bind(self))));
@@ -40,7 +49,110 @@ impl LoxInstance {
         })
     }
 
-    pub fn set(&self, name:Token, value:Literal) {
-        self.fields.borrow_mut().insert(name.lexeme, value);
+    // assignments go through a setter when there is one, a getter without a setter makes the property read-only
+    pub fn set(&self, name:Token, value:Literal, interpreter: &mut Interpreter) -> LoxResult<()> {
+        if name.lexeme.starts_with('#') {
+            return self.set_private(name, value, interpreter);
+        }
+        if let Some(setter) = self.class.find_setter(&name.lexeme) {
+            setter.bind(self).call(interpreter, vec![value])?;
+            return Ok(());
+        }
+        if self.is_read_only(&name.lexeme) {
+            return Err(LoxError::RuntimeError {
+                message: format!("Cannot set read-only property {}", name.lexeme),
+                token: Some(name),
+            });
+        }
+        self.set_field(&name.lexeme, value);
+        Ok(())
+    }
+
+    // private members are only looked up in the class that declares them, never in a subclass
+    fn get_private(&self, name: Token, interpreter: &mut Interpreter) -> LoxResult<Literal> {
+        let owner = self.class.private_owner(&name, interpreter)?;
+        if let Some(getter) = owner.members().getters.get(&name.lexeme) {
+            return getter.bind(self).call(interpreter, Vec::new());
+        }
+        let key = (owner.declaration(), name.lexeme.clone());
+        if let Some(value) = self.private_fields.borrow().get(&key) {
+            return Ok(value.clone());
+        }
+        if let Some(method) = owner.members().methods.get(&name.lexeme) {
+            return Ok(Literal::LoxCallable(Rc::new(method.bind(self))));
+        }
+        Err(LoxError::RuntimeError {
+            token: Some(name),
+            message: "Undefined property".to_string(),
+        })
+    }
+
+    fn set_private(&self, name: Token, value: Literal, interpreter: &mut Interpreter) -> LoxResult<()> {
+        let owner = self.class.private_owner(&name, interpreter)?;
+        let members = owner.members();
+        if let Some(setter) = members.setters.get(&name.lexeme) {
+            setter.bind(self).call(interpreter, vec![value])?;
+            return Ok(());
+        }
+        if members.getters.contains_key(&name.lexeme) {
+            return Err(LoxError::RuntimeError {
+                message: format!("Cannot set read-only property {}", name.lexeme),
+                token: Some(name),
+            });
+        }
+        self.private_fields.borrow_mut().insert((owner.declaration(), name.lexeme), value);
+        Ok(())
+    }
+
+    // a `let` default from a class body, stored without running a getter or setter
+    pub fn set_default(&self, name: &Token, value: Literal, interpreter: &Interpreter) -> LoxResult<()> {
+        if name.lexeme.starts_with('#') {
+            let owner = self.class.private_owner(name, interpreter)?;
+            self.private_fields.borrow_mut().insert((owner.declaration(), name.lexeme.clone()), value);
+        } else {
+            self.set_field(&name.lexeme, value);
+        }
+        Ok(())
+    }
+
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.class.find_getter(name).is_some() && self.class.find_setter(name).is_none()
+    }
+
+    // whether get() would find something, a getter, a field or a method
+    pub fn has_property(&self, name: &str) -> bool {
+        self.class.find_getter(name).is_some() || self.has_field(name) || self.class.find_method(name).is_some()
+    }
+
+    pub fn has_field(&self, name: &str) -> bool {
+        self.fields.borrow().contains_key(name)
+    }
+
+    // sorted, since fields are kept in a HashMap. private fields are kept apart and left out
+    pub fn field_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.fields.borrow().keys().cloned().collect();
+        names.sort();
+        names
     }
-}
\ No newline at end of file
+
+    pub fn class(&self) -> &Rc<LoxClass> {
+        &self.class
+    }
+
+    pub fn bound_method(&self, name: &str) -> Option<LoxFunction> {
+        self.class.find_method(name).map(|method| method.bind(self))
+    }
+
+    pub fn same(&self, other: &LoxInstance) -> bool {
+        Rc::ptr_eq(&self.fields, &other.fields)
+    }
+
+    pub fn class_name(&self) -> &str {
+        self.class.name()
+    }
+
+    // stores a field directly, skipping any setter
+    pub fn set_field(&self, name: &str, value: Literal) {
+        self.fields.borrow_mut().insert(name.to_string(), value);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::token::{AtomicLiteral, Literal};
+use std::collections::HashMap;
+
+// entries keep their insertion order so printing and iterating a map is deterministic
+#[derive(Clone, Default)]
+pub struct LoxMap {
+    entries: Vec<(AtomicLiteral, Literal)>,
+    index: HashMap<AtomicLiteral, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &AtomicLiteral) -> Option<Literal> {
+        self.index.get(key).map(|i| self.entries[*i].1.clone())
+    }
+
+    pub fn insert(&mut self, key: AtomicLiteral, value: Literal) {
+        match self.index.get(&key) {
+            Some(i) => self.entries[*i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn contains(&self, key: &AtomicLiteral) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[(AtomicLiteral, Literal)] {
+        &self.entries
+    }
+}
@@ -0,0 +1,108 @@
+use crate::environment::Environment;
+use crate::interpreter::Interpreter;
+use crate::lox_error::{LoxError, LoxResult};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::scanner;
+use crate::token::{Literal, Token};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+// a file that has been imported. it keeps its own globals alive, importers only see the exported names
+pub struct LoxModule {
+    pub path: PathBuf,
+    globals: Rc<RefCell<Environment>>,
+    exports: HashSet<String>,
+}
+
+impl LoxModule {
+    // reads an export through the module's globals, so later changes inside the module stay visible
+    pub fn get(&self, name: &Token) -> LoxResult<Literal> {
+        if !self.exports.contains(&name.lexeme) {
+            return Err(LoxError::RuntimeError {
+                token: Some(name.clone()),
+                message: format!("Module {} does not export {}", self.name(), name.lexeme),
+            });
+        }
+        self.globals.borrow().get(name)
+    }
+
+    pub fn name(&self) -> String {
+        display_name(&self.path)
+    }
+
+    // scans, parses, resolves and runs the file behind an import path the first time it is seen,
+    // later imports of the same file get the cached module
+    pub fn load(interpreter: &mut Interpreter, path_token: &Token) -> LoxResult<Rc<LoxModule>> {
+        let base = match interpreter.current_file.as_ref().and_then(|file| file.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from("."),
+        };
+        let path = base.join(&path_token.lexeme).canonicalize().map_err(|e| LoxError::RuntimeError {
+            token: Some(path_token.clone()),
+            message: format!("Cannot import {}: {}", path_token.lexeme, e),
+        })?;
+
+        if let Some(module) = interpreter.modules.get(&path) {
+            return Ok(module.clone());
+        }
+        if interpreter.loading.contains(&path) {
+            let mut chain: Vec<String> = interpreter.loading.iter().map(|p| display_name(p)).collect();
+            chain.push(display_name(&path));
+            return Err(LoxError::RuntimeError {
+                token: Some(path_token.clone()),
+                message: format!("Import cycle detected: {}", chain.join(" -> ")),
+            });
+        }
+
+        let source = std::fs::read_to_string(&path).map_err(|e| LoxError::RuntimeError {
+            token: Some(path_token.clone()),
+            message: format!("Cannot import {}: {}", path_token.lexeme, e),
+        })?;
+        let statements = scanner::scanner(&source)
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .map_err(|e| LoxError::GeneralError {
+                message: format!("In {}: {}", path.display(), e),
+            })?;
+
+        // the module runs against its own globals and resolver distances, the importer's are put back afterwards
+        let globals = Interpreter::global_environment();
+        let previous_globals = std::mem::replace(&mut interpreter.global, globals.clone());
+        let previous_env = std::mem::replace(&mut interpreter.env, globals.clone());
+        let previous_locals = std::mem::replace(&mut interpreter.local, Rc::new(RefCell::new(HashMap::new())));
+        let previous_file = interpreter.current_file.replace(path.clone());
+        interpreter.loading.push(path.clone());
+
+        let mut resolver = Resolver::new(interpreter);
+        let resolved = resolver.resolve(&statements);
+        let warnings = std::mem::take(&mut resolver.warnings);
+        let name = display_name(&path);
+        interpreter.warnings.extend(warnings.into_iter().map(|warning| format!("In {}: {}", name, warning)));
+        let result = resolved.and_then(|_| interpreter.interpreter(&statements));
+
+        interpreter.loading.pop();
+        interpreter.global = previous_globals;
+        interpreter.env = previous_env;
+        interpreter.local = previous_locals;
+        interpreter.current_file = previous_file;
+        result?;
+
+        let exports = statements
+            .iter()
+            .filter_map(|statement| statement.exported_name())
+            .map(|name| name.lexeme.clone())
+            .collect();
+        let module = Rc::new(LoxModule { path: path.clone(), globals, exports });
+        interpreter.modules.insert(path, module.clone());
+        Ok(module)
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => path.display().to_string(),
+    }
+}
@@ -0,0 +1,29 @@
+use crate::loxfuncs::LoxFunction;
+use crate::token::Token;
+use std::collections::HashMap;
+
+// a named set of methods that classes pull in with `with`, it can't be instantiated on its own
+pub struct LoxTrait {
+    name: Token,
+    methods: HashMap<String, LoxFunction>,
+    // bodiless methods the including class must define
+    abstract_methods: Vec<String>,
+}
+
+impl LoxTrait {
+    pub fn new(name: Token, methods: HashMap<String, LoxFunction>, abstract_methods: Vec<String>) -> Self {
+        LoxTrait { name, methods, abstract_methods }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name.lexeme
+    }
+
+    pub fn methods(&self) -> &HashMap<String, LoxFunction> {
+        &self.methods
+    }
+
+    pub fn abstract_methods(&self) -> &[String] {
+        &self.abstract_methods
+    }
+}
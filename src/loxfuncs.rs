@@ -1,7 +1,10 @@
+use crate::lox_class::LoxClass;
 use crate::lox_instance::LoxInstance;
 use crate::statement::{FunctionProps,StatementType};
 use crate::{callable::Callable, interpreter::Interpreter, token::Literal, environment::Environment};
+use crate::expression::ExpressionType;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use crate::token::Token;
 use crate::lox_error::{LoxError, LoxResult};
@@ -12,6 +15,11 @@ pub struct LoxFunction {
     params: Vec<Token>,             
     body: Rc<StatementType>,        
     closure: Rc<RefCell<Environment>>,
+    // the globals and resolver distances of the module the function was declared in
+    globals: Rc<RefCell<Environment>>,
+    locals: Rc<RefCell<HashMap<ExpressionType, usize>>>,
+    // the class whose body the function was created in, it decides which private members the function can reach
+    class: Option<Rc<Token>>,
     is_initializer: bool
 }
 impl LoxFunction {
@@ -21,20 +29,35 @@ impl LoxFunction {
             params: func_props.params.clone(),   
             body: func_props.body.clone(),       
             closure: interpreter.env.clone(),
+            globals: interpreter.global.clone(),
+            locals: interpreter.local.clone(),
+            class: interpreter.current_class.clone(),
             is_initializer
         };
     }
 
     pub fn bind(&self, instance: &LoxInstance) -> LoxFunction {
+        self.bind_this(Literal::Instance(instance.to_owned()))
+    }
+
+    // static methods see the class they were called on as `this`
+    pub fn bind_class(&self, class: &LoxClass) -> LoxFunction {
+        self.bind_this(Literal::LoxCallable(Rc::new(class.clone())))
+    }
+
+    fn bind_this(&self, this: Literal) -> LoxFunction {
         let mut env = Environment::new(Some(self.closure.clone()));
         let _ = env.define(
             Token::new(crate::token::TokenType::THIS, "this".to_string(), 0, crate::token::AtomicLiteral::Nil),
-            Literal::Instance(instance.to_owned()));
+            this);
         LoxFunction { 
             _name: self._name.clone(), 
             params: self.params.clone(), 
             body: self.body.clone(), 
             closure: Rc::new(RefCell::new(env)),
+            globals: self.globals.clone(),
+            locals: self.locals.clone(),
+            class: self.class.clone(),
             is_initializer: self.is_initializer 
         }    
     }
@@ -56,12 +79,20 @@ impl Callable for LoxFunction {
             closure.borrow_mut().define(param.clone(), arg)?;
         }
 
+        // a function imported from another module keeps resolving names against that module
+        let previous_globals = std::mem::replace(&mut interpreter.global, self.globals.clone());
+        let previous_locals = std::mem::replace(&mut interpreter.local, self.locals.clone());
+        let previous_class = std::mem::replace(&mut interpreter.current_class, self.class.clone());
         let result = StatementType::evaluate_func_block(&self.body, closure, interpreter);
+        interpreter.global = previous_globals;
+        interpreter.local = previous_locals;
+        interpreter.current_class = previous_class;
         
+        // init always hands back the instance, also when it is called again directly or leaves with a bare return
         match result {
             Ok(()) => {
                 if self.is_initializer {
-                    self.closure.borrow_mut().get_at(0, "this")?;
+                    return self.closure.borrow().get_at(0, "this");
                 }
                 Ok(Literal::Basic(crate::token::AtomicLiteral::Nil))
             },
@@ -69,11 +100,11 @@ impl Callable for LoxFunction {
                 match e {
                     LoxError::ReturnValue(v) => {
                         if self.is_initializer {
-                            self.closure.borrow_mut().get_at(0, "this")?;
+                            return self.closure.borrow().get_at(0, "this");
                         }
                         Ok(v)
                     },
-                    _ => {return Err(e)},
+                    _ => Err(e),
                 }
             }
         }
@@ -82,6 +113,14 @@ impl Callable for LoxFunction {
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
+
+    fn describe(&self) -> String {
+        if self._name.tokentype == crate::token::TokenType::FUNCTION {
+            format!("<fn anonymous/{}>", self.params.len())
+        } else {
+            format!("<fn {}/{}>", self._name.lexeme, self.params.len())
+        }
+    }
 }
 
 
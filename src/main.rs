@@ -8,11 +8,15 @@ mod expression;
 mod statement;
 mod callable;
 mod clock;
+mod builtins;
 mod lox_error;
 mod lox_class;
 mod resolver;
 mod loxfuncs;
 mod lox_instance;
+mod lox_map;
+mod lox_module;
+mod lox_trait;
 use terminal_reader::terminal_reader;
 use lox_error::{LoxResult};
 use std::env;
@@ -38,7 +42,7 @@ fn main() {
         let input = terminal_reader();
         match input {
             Ok(result) => {
-                if let Err(e) = run(&result) {
+                if let Err(e) = run(&result, None) {
                     eprintln!("{}", e)
                 }
             }
@@ -50,20 +54,25 @@ fn main() {
 fn file_reader(path: &str)-> LoxResult<()> {
     let source = std::fs::read_to_string(path);
     match source {
-        Ok(result) => run(&result),
-        Err(e) => {
-            return Err(LoxError::GeneralError { message: format!("Failed to read file: {}", e) })
-        }
+        Ok(result) => run(&result, Some(std::path::PathBuf::from(path))),
+        Err(e) => Err(LoxError::GeneralError { message: format!("Failed to read file: {}", e) }),
     }
 }
 
-fn run(source: &str) -> LoxResult<()> {
+fn run(source: &str, path: Option<std::path::PathBuf>) -> LoxResult<()> {
     let tokens = scanner::scanner(source)?;
     let mut parser = parser::Parser::new(tokens);
     let statements = parser.parse()?;
-    let mut interpreter = interpreter::Interpreter::new();
+    let mut interpreter = interpreter::Interpreter::new(path);
     let mut resolver = resolver::Resolver::new(&mut interpreter);
     resolver.resolve(&statements)?;
-    interpreter.interpreter(&statements)?;
-    Ok(())
+    for warning in &resolver.warnings {
+        eprintln!("{}", warning);
+    }
+    let result = interpreter.interpreter(&statements);
+    // imported modules are resolved while the program runs, their warnings are reported once it stops
+    for warning in &interpreter.warnings {
+        eprintln!("{}", warning);
+    }
+    result
 }
@@ -29,6 +29,13 @@ impl Parser {
         !self.is_at_end() && &self.peek().tokentype == tokentype
     }
 
+    fn check_next(&self, tokentype: &TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => &token.tokentype == tokentype,
+            None => false,
+        }
+    }
+
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -75,7 +82,8 @@ impl Parser {
             match self.peek().tokentype {
                 TokenType::CLASS | TokenType::FUNCTION | TokenType::LET | 
                 TokenType::FOR | TokenType::IF | TokenType::WHILE | 
-                TokenType::PRINT | TokenType::RETURN => return,
+                TokenType::PRINT | TokenType::RETURN | TokenType::TRY | TokenType::THROW |
+                TokenType::IMPORT | TokenType::EXPORT | TokenType::TRAIT => return,
                 _ => {}
             }
     
@@ -102,16 +110,49 @@ impl Parser {
     fn declaration(&mut self) -> LoxResult<StatementType> {
         let result = if self.match_token(&[TokenType::LET]) {
             self.var_declaration()
-        } else if self.match_token(&[TokenType::FUNCTION]) {
+        } else if self.check_token(&TokenType::FUNCTION) && self.check_next(&TokenType::IDENTIFIER) {
+            self.advance();
             self.function_declaration(FunctionType::Function)
         }else if self.match_token(&[TokenType::CLASS]){
             self.class_declaration()
+        } else if self.match_token(&[TokenType::TRAIT]) {
+            self.trait_declaration()
+        } else if self.match_token(&[TokenType::IMPORT]) {
+            self.import_declaration()
+        } else if self.match_token(&[TokenType::EXPORT]) {
+            self.export_declaration()
         } else {
             self.statement()
         };
         result
     }
 
+    fn import_declaration(&mut self) -> LoxResult<StatementType> {
+        let keyword = self.previous();
+        let path = self.consume(TokenType::STRING, "Expected a file path string after import")?;
+        self.consume(TokenType::AS, "Expected 'as' after the import path")?;
+        let name = self.consume(TokenType::IDENTIFIER, "Expected a module name after 'as'")?;
+        self.consume(TokenType::SEMICOLON, "Expected ; after import")?;
+        Ok(StatementType::ImportStatement(ImportProps { keyword, path, name }))
+    }
+
+    fn export_declaration(&mut self) -> LoxResult<StatementType> {
+        let keyword = self.previous();
+        let declaration = if self.match_token(&[TokenType::LET]) {
+            self.var_declaration()?
+        } else if self.check_token(&TokenType::FUNCTION) && self.check_next(&TokenType::IDENTIFIER) {
+            self.advance();
+            self.function_declaration(FunctionType::Function)?
+        } else if self.match_token(&[TokenType::CLASS]) {
+            self.class_declaration()?
+        } else if self.match_token(&[TokenType::TRAIT]) {
+            self.trait_declaration()?
+        } else {
+            return Err(self.error(self.peek(), "Expected a let, fn, class or trait declaration after export"));
+        };
+        Ok(StatementType::ExportStatement(ExportProps { keyword, declaration: Box::new(declaration) }))
+    }
+
     fn class_declaration(&mut self) -> LoxResult<StatementType> {
         let name = self.consume(TokenType::IDENTIFIER, "Expected class name")?;
         let mut super_class = None;
@@ -119,21 +160,161 @@ impl Parser {
             self.consume(TokenType::IDENTIFIER, "Expected a superclass name")?;
             super_class = Some(ExpressionType::Variable(self.previous()));
         }
+        let mut traits = Vec::new();
+        if self.match_token(&[TokenType::WITH]) {
+            loop {
+                traits.push(ExpressionType::Variable(self.consume(TokenType::IDENTIFIER, "Expected a trait name")?));
+                if !self.match_token(&[TokenType::COMMA]) {
+                    break;
+                }
+            }
+        }
         self.consume(TokenType::LEFTBRACE, "Expected '{' before class body")?;
         let mut methods: Vec<StatementType> = Vec::new();
+        let mut static_methods: Vec<StatementType> = Vec::new();
+        let mut static_fields: Vec<LetExpressionProps> = Vec::new();
+        let mut getters: Vec<StatementType> = Vec::new();
+        let mut setters: Vec<StatementType> = Vec::new();
+        let mut abstract_methods: Vec<Token> = Vec::new();
+        let mut fields: Vec<LetExpressionProps> = Vec::new();
         while !self.check_token(&TokenType::RIGHTBRACE) && !self.is_at_end() {
-            methods.push(self.function_declaration(FunctionType::Method)?);
+            if self.check_accessor("get") {
+                self.advance();
+                let name = self.consume(TokenType::IDENTIFIER, "Expected property name after get")?;
+                self.consume(TokenType::LEFTBRACE, "Expected '{' before getter body")?;
+                let body = self.block_statement()?;
+                getters.push(StatementType::Function(FunctionProps {
+                    name,
+                    params: Vec::new(),
+                    body: Rc::new(body),
+                }));
+            } else if self.check_accessor("set") {
+                self.advance();
+                let name = self.consume(TokenType::IDENTIFIER, "Expected property name after set")?;
+                let (params, body) = self.function_body()?;
+                if params.len() != 1 {
+                    return Err(self.error(&name, "A setter takes exactly one parameter"));
+                }
+                setters.push(StatementType::Function(FunctionProps {
+                    name,
+                    params,
+                    body: Rc::new(body),
+                }));
+            } else if self.match_token(&[TokenType::STATIC]) {
+                if self.match_token(&[TokenType::LET]) {
+                    if let StatementType::LetStatement(field) = self.var_declaration()? {
+                        static_fields.push(field);
+                    }
+                } else {
+                    // `static fn name()` and `static name()` both declare a static method
+                    self.match_token(&[TokenType::FUNCTION]);
+                    static_methods.push(self.function_declaration(FunctionType::Method)?);
+                }
+            } else if self.match_token(&[TokenType::LET]) {
+                if let StatementType::LetStatement(field) = self.var_declaration()? {
+                    fields.push(field);
+                }
+            } else {
+                self.method_declaration(&mut methods, &mut abstract_methods)?;
+            }
         }
         self.consume(TokenType::RIGHTBRACE, "Expected '}' after class body")?;
-        Ok(StatementType::ClassStatement(ClassProps{
+        let private_names = methods
+            .iter()
+            .chain(&static_methods)
+            .chain(&getters)
+            .chain(&setters)
+            .filter_map(|member| match member {
+                StatementType::Function(func) => Some(&func.name),
+                _ => None,
+            })
+            .chain(&abstract_methods)
+            .chain(fields.iter().chain(&static_fields).map(|field| &field.name))
+            .filter(|name| name.lexeme.starts_with('#'))
+            .map(|name| name.lexeme.clone())
+            .collect();
+        let field_initializer = field_initializer(&name, fields);
+        Ok(StatementType::ClassStatement(Box::new(ClassProps{
             name,
             methods,
+            static_methods,
+            static_fields,
+            getters,
+            setters,
+            abstract_methods,
+            field_initializer,
+            private_names,
+            traits,
             superclass:super_class
-        }))
+        })))
+    }
+
+    fn trait_declaration(&mut self) -> LoxResult<StatementType> {
+        let name = self.consume(TokenType::IDENTIFIER, "Expected trait name")?;
+        self.consume(TokenType::LEFTBRACE, "Expected '{' before trait body")?;
+        let mut methods: Vec<StatementType> = Vec::new();
+        let mut abstract_methods: Vec<Token> = Vec::new();
+        while !self.check_token(&TokenType::RIGHTBRACE) && !self.is_at_end() {
+            self.method_declaration(&mut methods, &mut abstract_methods)?;
+        }
+        self.consume(TokenType::RIGHTBRACE, "Expected '}' after trait body")?;
+        Ok(StatementType::TraitStatement(TraitProps { name, methods, abstract_methods }))
+    }
+
+    // a method in a class or trait body, `name(params);` without a body only declares it
+    fn method_declaration(&mut self, methods: &mut Vec<StatementType>, abstract_methods: &mut Vec<Token>) -> LoxResult<()> {
+        let name = self.consume(TokenType::IDENTIFIER, "exprected identifier")?;
+        let params = self.parameters()?;
+        if self.match_token(&[TokenType::SEMICOLON]) {
+            abstract_methods.push(name);
+            return Ok(());
+        }
+        self.consume(TokenType::LEFTBRACE, "expected { at the start of body")?;
+        let body = self.block_statement()?;
+        methods.push(StatementType::Function(FunctionProps {
+            name,
+            params,
+            body: Rc::new(body),
+        }));
+        Ok(())
+    }
+
+    // `get` and `set` only start an accessor when a property name follows, so methods named get or set still parse
+    fn check_accessor(&self, word: &str) -> bool {
+        self.check_token(&TokenType::IDENTIFIER) && self.peek().lexeme == word && self.check_next(&TokenType::IDENTIFIER)
     }
 
     fn function_declaration(&mut self, _func_type: FunctionType) -> LoxResult<StatementType> {
         let name = self.consume(TokenType::IDENTIFIER, "exprected identifier")?;
+        let (params, body) = self.function_body()?;
+        Ok(StatementType::Function(FunctionProps { 
+            name, 
+            params, 
+            body: Rc::new(body) 
+        }))
+    }
+
+    // an anonymous `fn (params) { body }`, the `fn` keyword doubles as its name token
+    fn lambda(&mut self) -> LoxResult<ExpressionType> {
+        let keyword = self.previous();
+        let (params, body) = self.function_body()?;
+        Ok(ExpressionType::Lambda(LambdaArgs {
+            function: Rc::new(FunctionProps {
+                name: keyword,
+                params,
+                body: Rc::new(body),
+            }),
+        }))
+    }
+
+    fn function_body(&mut self) -> LoxResult<(Vec<Token>, StatementType)> {
+        let tokens = self.parameters()?;
+        self.consume(TokenType::LEFTBRACE, "expected { at the start of body")?;
+        let body = self.block_statement()?;
+        Ok((tokens, body))
+    }
+
+    fn parameters(&mut self) -> LoxResult<Vec<Token>> {
         self.consume(TokenType::LEFTPAREN, "expected a (")?;
         let mut tokens : Vec<Token> = Vec::new();
         if !self.check_token(&TokenType::RIGHTPAREN) {
@@ -143,14 +324,7 @@ impl Parser {
             }
         }
         self.consume(TokenType::RIGHTPAREN, "expected a ) at the end of arguments")?;
-        self.consume(TokenType::LEFTBRACE, "expected { at the start of body")?;
-        let body = self.block_statement()?;
-
-        Ok(StatementType::Function(FunctionProps { 
-            name, 
-            params: tokens, 
-            body: Rc::new(body) 
-        }))
+        Ok(tokens)
     }
 
     fn var_declaration(&mut self) -> LoxResult<StatementType> {
@@ -179,6 +353,55 @@ impl Parser {
         }))
     }
 
+    fn throw_statement(&mut self) -> LoxResult<StatementType> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(TokenType::SEMICOLON, "Expected ; after thrown value")?;
+        Ok(StatementType::ThrowStatement(ThrowProps { keyword, value }))
+    }
+
+    fn try_statement(&mut self) -> LoxResult<StatementType> {
+        let keyword = self.previous();
+        self.consume(TokenType::LEFTBRACE, "Expected { after try")?;
+        let body = self.block_statement()?;
+
+        let mut catch_name = None;
+        let mut catch_body = None;
+        if self.match_token(&[TokenType::CATCH]) {
+            self.consume(TokenType::LEFTPAREN, "Expected ( after catch")?;
+            catch_name = Some(self.consume(TokenType::IDENTIFIER, "Expected a name for the caught error")?);
+            self.consume(TokenType::RIGHTPAREN, "Expected ) after the caught error's name")?;
+            self.consume(TokenType::LEFTBRACE, "Expected { after catch")?;
+            catch_body = Some(Box::new(self.block_statement()?));
+        }
+
+        let mut finally_body = None;
+        if self.match_token(&[TokenType::FINALLY]) {
+            self.consume(TokenType::LEFTBRACE, "Expected { after finally")?;
+            finally_body = Some(Box::new(self.block_statement()?));
+        }
+
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(self.error(&keyword, "Expected catch or finally after try block"));
+        }
+        Ok(StatementType::TryStatement(TryProps {
+            body: Box::new(body),
+            catch_name,
+            catch_body,
+            finally_body,
+        }))
+    }
+
+    fn loop_control_statement(&mut self) -> LoxResult<StatementType> {
+        let keyword = self.previous();
+        self.consume(TokenType::SEMICOLON, &format!("Expected ; after {}", keyword.lexeme))?;
+        if keyword.tokentype == TokenType::BREAK {
+            Ok(StatementType::BreakStatement(keyword))
+        } else {
+            Ok(StatementType::ContinueStatement(keyword))
+        }
+    }
+
     fn statement(&mut self) -> LoxResult<StatementType> {
         if self.match_token(&[TokenType::PRINT]) {
             return self.print_statement();
@@ -192,6 +415,12 @@ impl Parser {
             return self.for_statement();
         }else if self.match_token(&[TokenType::RETURN]){
             return self.return_statement();
+        } else if self.match_token(&[TokenType::BREAK, TokenType::CONTINUE]) {
+            return self.loop_control_statement();
+        } else if self.match_token(&[TokenType::THROW]) {
+            return self.throw_statement();
+        } else if self.match_token(&[TokenType::TRY]) {
+            return self.try_statement();
         } else {
             return self.expression_statement();
         }
@@ -222,18 +451,13 @@ impl Parser {
     
         let mut body: StatementType = self.statement()?;
     
-        if let Some(increment_result) = increment {
-            let args: Vec<StatementType> =
-                vec![body, StatementType::ExpressionStatement(increment_result)];
-            body = StatementType::BlockStatement(args);
-        }
-    
         if condition.is_none() {
             condition = Some(ExpressionType::Literal(AtomicLiteral::Bool(true)));
         }
         body = StatementType::WhileStatement(WhileProps {
             condition: condition.unwrap(),
             statement: Box::new(body),
+            increment,
         });
     
         if let Some(initializer_result) = _initializer {
@@ -253,6 +477,7 @@ impl Parser {
         Ok(StatementType::WhileStatement(WhileProps {
             statement: Box::new(statement),
             condition: condition,
+            increment: None,
         }))
     }
     
@@ -324,7 +549,7 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> LoxResult<ExpressionType> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
     
         if self.match_token(&[TokenType::EQUAL]) {
             let equals = self.previous();
@@ -337,28 +562,94 @@ impl Parser {
                         value: Box::new(value),
                     }))
                 }
-                ExpressionType::Get(get ) => {
+                ExpressionType::Get(get) => {
                     Ok(ExpressionType::Set(SetArgs {
                         name: get.name,
                         object: get.object,
                         value: Box::new(value),
                     }))
                 }
+                ExpressionType::Index(index) => {
+                    Ok(ExpressionType::IndexSet(IndexSetArgs {
+                        object: index.object,
+                        bracket: index.bracket,
+                        index: index.index,
+                        value: Box::new(value),
+                    }))
+                }
                 _ => Err(LoxError::ParseError {
                     token: equals,
                     message: "Invalid assignment target".to_string(),
                 })
             }
+        } else if self.match_token(&[
+            TokenType::PLUSEQUAL,
+            TokenType::MINUSEQUAL,
+            TokenType::STAREQUAL,
+            TokenType::SLASHEQUAL,
+            TokenType::MODULOEQUAL,
+        ]) {
+            let operator = self.previous();
+            let value = self.assignment()?;
+            self.compound_assignment(expr, operator, value)
         } else {
             Ok(expr)
         }
     }
 
+    fn compound_assignment(&self, target: ExpressionType, operator: Token, value: ExpressionType) -> LoxResult<ExpressionType> {
+        if !is_assignable(&target) {
+            return Err(LoxError::ParseError {
+                token: operator,
+                message: "Invalid assignment target".to_string(),
+            });
+        }
+        Ok(ExpressionType::CompoundAssign(CompoundAssignArgs {
+            target: Box::new(target),
+            operator,
+            value: Box::new(value),
+        }))
+    }
+
+    // cond ? a : b, the else branch nests to the right so a ? b : c ? d : e needs no parentheses
+    fn conditional(&mut self) -> LoxResult<ExpressionType> {
+        let condition = self.coalesce()?;
+
+        if self.match_token(&[TokenType::QUESTION]) {
+            let question = self.previous();
+            let then_branch = self.expression()?;
+            self.consume(TokenType::COLON, "Expected ':' in conditional expression")?;
+            let else_branch = self.assignment()?;
+            return Ok(ExpressionType::Conditional(ConditionalArgs {
+                condition: Box::new(condition),
+                question,
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> LoxResult<ExpressionType> {
+        let mut expr = self.or()?;
+
+        while self.match_token(&[TokenType::QUESTIONQUESTION]) {
+            let operator = self.previous();
+            let right = self.or()?;
+            expr = ExpressionType::Logical(BinaryExpression {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        Ok(expr)
+    }
+
     fn or(&mut self) -> LoxResult<ExpressionType> {
         let mut expr = self.and()?;
     
         while self.match_token(&[TokenType::OR]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right = self.and()?;
             expr = ExpressionType::Logical(BinaryExpression {
                 left: Box::new(expr),
@@ -373,7 +664,7 @@ impl Parser {
         let mut expr = self.equality()?;
     
         while self.match_token(&[TokenType::AND]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right = self.equality()?;
             expr = ExpressionType::Logical(BinaryExpression {
                 left: Box::new(expr),
@@ -388,7 +679,7 @@ impl Parser {
         let mut expr = self.comparison()?;
 
         while self.match_token(&[TokenType::BANGEQUAL, TokenType::EQUALEQUAL]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right: ExpressionType = self.comparison()?;
             expr = ExpressionType::Binary(BinaryExpression {
                 left: Box::new(expr),
@@ -406,9 +697,10 @@ impl Parser {
             TokenType::GREATEREQUAL,
             TokenType::LESS,
             TokenType::LESSEQUAL,
+            TokenType::IS,
         ];
         while self.match_token(&types) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right: ExpressionType = self.term()?;
             expr = ExpressionType::Binary(BinaryExpression {
                 left: Box::new(expr),
@@ -423,7 +715,7 @@ impl Parser {
         let mut expr = self.factor()?;
 
         while self.match_token(&[TokenType::PLUS, TokenType::MINUS]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right: ExpressionType = self.factor()?;
             expr = ExpressionType::Binary(BinaryExpression {
                 left: Box::new(expr),
@@ -438,7 +730,7 @@ impl Parser {
         let mut expr = self.unary()?;
 
         while self.match_token(&[TokenType::STAR, TokenType::SLASH, TokenType::MODULO]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right: ExpressionType = self.unary()?;
             expr = ExpressionType::Binary(BinaryExpression {
                 left: Box::new(expr),
@@ -451,12 +743,17 @@ impl Parser {
 
     fn unary(&mut self) -> LoxResult<ExpressionType> {
         if self.match_token(&[TokenType::BANG, TokenType::MINUS]) {
-            let operator = self.previous().tokentype;
+            let operator = self.previous();
             let right = self.unary()?;
             Ok(ExpressionType::Unary(UnaryExpression {
                 operator: operator,
                 right: Box::new(right),
             }))
+        } else if self.match_token(&[TokenType::INCREMENTOR, TokenType::DECREMENTOR]) {
+            // ++x is x += 1, it evaluates to the updated value
+            let operator = self.previous();
+            let target = self.unary()?;
+            self.compound_assignment(target, operator, ExpressionType::Literal(AtomicLiteral::Number(1)))
         } else {
             self.postfix()
         }
@@ -464,40 +761,85 @@ impl Parser {
 
     fn postfix(&mut self) -> LoxResult<ExpressionType> {
         let mut expr = self.primary()?;
+        let mut optional_chain = false;
     
         loop {
             if self.match_token(&[TokenType::LEFTPAREN]) {
                 expr = self.finish_call(expr)?;
-            } else if self.match_token(&[TokenType::DOT]) {
+            } else if self.match_token(&[TokenType::DOT, TokenType::QUESTIONDOT]) {
+                let optional = self.previous().tokentype == TokenType::QUESTIONDOT;
+                optional_chain |= optional;
                 let name = self.consume(TokenType::IDENTIFIER, "Expected property name after '.'")?;
                 expr = ExpressionType::Get(GetArgs {
                     name,
                     object: Box::new(expr),
+                    optional,
+                });
+            } else if self.match_token(&[TokenType::LEFTBRACKET]) {
+                let bracket = self.previous();
+                let index = self.expression()?;
+                self.consume(TokenType::RIGHTBRACKET, "Expected ']' after index")?;
+                expr = ExpressionType::Index(IndexArgs {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
                 });
             } else {
                 break;
             }
         }
+        // everything after the first `?.` is skipped when it meets nil, `a?.b.c` is nil for a nil `a`
+        if optional_chain {
+            expr = ExpressionType::OptionalChain(Box::new(expr));
+        }
 
         if self.match_token(&[TokenType::INCREMENTOR, TokenType::DECREMENTOR]) {
-            match expr {
-                ExpressionType::Variable(_) => {
-                    let operator = self.previous().tokentype;
-                    Ok(ExpressionType::Postfix(PostfixExpression {
-                        expr: Box::new(expr),
-                        operator,
-                    }))
-                }
-                _ => Err(LoxError::ParseError {
-                    token: self.previous(),
+            let operator = self.previous();
+            if !is_assignable(&expr) {
+                return Err(LoxError::ParseError {
+                    token: operator,
                     message: "Invalid target for postfix operator".to_string(),
-                })
+                });
             }
+            Ok(ExpressionType::Postfix(PostfixExpression {
+                expr: Box::new(expr),
+                operator,
+            }))
         } else {
             Ok(expr)
         }
     }
 
+    fn list(&mut self) -> LoxResult<ExpressionType> {
+        let bracket = self.previous();
+        let mut elements: Vec<ExpressionType> = Vec::new();
+        while !self.check_token(&TokenType::RIGHTBRACKET) && !self.is_at_end() {
+            elements.push(self.expression()?);
+            if !self.match_token(&[TokenType::COMMA]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RIGHTBRACKET, "Expected ']' after list elements")?;
+        Ok(ExpressionType::List(ListArgs { bracket, elements }))
+    }
+
+    // only reached in expression position, a '{' that starts a statement is always a block
+    fn map(&mut self) -> LoxResult<ExpressionType> {
+        let brace = self.previous();
+        let mut entries: Vec<(ExpressionType, ExpressionType)> = Vec::new();
+        while !self.check_token(&TokenType::RIGHTBRACE) && !self.is_at_end() {
+            let key = self.expression()?;
+            self.consume(TokenType::COLON, "Expected ':' after map key")?;
+            let value = self.expression()?;
+            entries.push((key, value));
+            if !self.match_token(&[TokenType::COMMA]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RIGHTBRACE, "Expected '}' after map entries")?;
+        Ok(ExpressionType::Map(MapArgs { brace, entries }))
+    }
+
     fn primary(&mut self) -> LoxResult<ExpressionType> {
         if self.match_token(&[TokenType::FALSE]) {
             Ok(ExpressionType::Literal(AtomicLiteral::Bool(false)))
@@ -506,15 +848,22 @@ impl Parser {
         } else if self.match_token(&[TokenType::NIL]) {
             Ok(ExpressionType::Literal(AtomicLiteral::Nil))
         } else if self.match_token(&[TokenType::NUMBER]) {
-            Ok(ExpressionType::Literal(AtomicLiteral::Number(
-                self.previous().literal().parse().unwrap(),
-            )))
+            match self.previous().literal {
+                Some(number) => Ok(ExpressionType::Literal(number)),
+                None => Err(self.error(&self.previous(), "Expected a number literal")),
+            }
         } else if self.match_token(&[TokenType::STRING]) {
             Ok(ExpressionType::Literal(AtomicLiteral::String(self.previous().literal().clone())))
         } else if self.match_token(&[TokenType::LEFTPAREN]) {
             let expr = self.expression()?;
             self.consume(TokenType::RIGHTPAREN, "Expect ')' after expression.")?;
             Ok(ExpressionType::Grouping(Box::new(expr)))
+        } else if self.match_token(&[TokenType::FUNCTION]) {
+            self.lambda()
+        } else if self.match_token(&[TokenType::LEFTBRACKET]) {
+            self.list()
+        } else if self.match_token(&[TokenType::LEFTBRACE]) {
+            self.map()
         } else if self.match_token(&[TokenType::IDENTIFIER]) {
             Ok(ExpressionType::Variable(self.previous()))
         } else if self.match_token(&[TokenType::THIS]) {
@@ -535,17 +884,18 @@ pub fn print_expr(expr: &ExpressionType) -> String {
     match expr {
         ExpressionType::Binary(b) => format!(
             "({} {} {})",
-            b.operator,
+            b.operator.tokentype,
             print_expr(&b.left),
             print_expr(&b.right),
         ),
 
-        ExpressionType::Unary(u) => format!("({} {})", u.operator, print_expr(&u.right),),
+        ExpressionType::Unary(u) => format!("({} {})", u.operator.tokentype, print_expr(&u.right),),
 
         ExpressionType::Grouping(expr) => format!("(group {})", print_expr(expr),),
 
         ExpressionType::Literal(lit) => match lit {
                 AtomicLiteral::Number(n) => n.to_string(),
+                AtomicLiteral::Float(n) => crate::token::format_float(*n),
                 AtomicLiteral::String(s) => s.clone(),
                 AtomicLiteral::Bool(b) => b.to_string(),
                 AtomicLiteral::Nil => "nil".to_string(),
@@ -561,10 +911,17 @@ pub fn print_expr(expr: &ExpressionType) -> String {
         ExpressionType::Logical(v) => format!(
             "{} {} {}",
             print_expr(&v.left),
-            v.operator,
+            v.operator.tokentype,
             print_expr(&v.right)
         ),
-        ExpressionType::Postfix(post) => format!("{} {}", print_expr(&post.expr), post.operator),
+        ExpressionType::Postfix(post) => format!("{} {}", print_expr(&post.expr), post.operator.tokentype),
+        ExpressionType::OptionalChain(chain) => format!("(?. {})", print_expr(chain)),
+        ExpressionType::Conditional(c) => format!(
+            "(? {} {} {})",
+            print_expr(&c.condition),
+            print_expr(&c.then_branch),
+            print_expr(&c.else_branch)
+        ),
         ExpressionType::Call(called) => format!(
             "{} {}",
             print_expr(&called.callee),
@@ -574,3 +931,32 @@ pub fn print_expr(expr: &ExpressionType) -> String {
 
     }
 }
+
+// the `let` fields of a class body become one method that stores each default on `this`, in declaration order.
+// the defaults are plain fields, a getter or setter of the same name isn't involved
+fn field_initializer(class_name: &Token, fields: Vec<LetExpressionProps>) -> Option<FunctionProps> {
+    if fields.is_empty() {
+        return None;
+    }
+    let assignments = fields
+        .into_iter()
+        .map(|field| {
+            let this = Token::new(TokenType::THIS, "this".to_string(), field.name.line, AtomicLiteral::Nil);
+            StatementType::ExpressionStatement(ExpressionType::FieldDefault(SetArgs {
+                name: field.name,
+                object: Box::new(ExpressionType::This(this)),
+                value: field.initializer,
+            }))
+        })
+        .collect();
+    Some(FunctionProps {
+        name: class_name.clone(),
+        params: Vec::new(),
+        body: Rc::new(StatementType::BlockStatement(assignments)),
+    })
+}
+
+// variables, fields and elements can be written to, anything in an optional chain like `a?.b` can't
+fn is_assignable(expr: &ExpressionType) -> bool {
+    matches!(expr, ExpressionType::Variable(_) | ExpressionType::Index(_) | ExpressionType::Get(_))
+}
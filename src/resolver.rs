@@ -1,16 +1,26 @@
-use crate::expression::{ClassType, ExpressionType, FunctionType};
+use crate::expression::{ClassType, ExpressionType, FunctionType, LoopType};
 use crate::interpreter::Interpreter;
 use crate::lox_error::{LoxError, LoxResult};
 use crate::statement::FunctionProps;
 use crate::token::Token;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
     pub scopes: RefCell<Vec<Scope>>,
     pub current_function: FunctionType,
-    pub current_class: ClassType
+    pub current_class: ClassType,
+    pub current_loop: LoopType,
+    // per scope, the global one first: for each name declared there, whether it is a class whose
+    // instances get an init (their own or an inherited one). None for names that aren't classes
+    class_inits: Vec<HashMap<String, Option<bool>>>,
+    // set when `super.init` shows up, checked after resolving a subclass init
+    pub calls_super_init: bool,
+    // found while resolving, printed by whoever runs the code
+    pub warnings: Vec<String>,
+    // the private `#names` of each class body being resolved, innermost last
+    pub private_names: Vec<HashSet<String>>,
 }
 
 type Scope = HashMap<String, bool>;
@@ -34,23 +44,35 @@ impl<'a> Resolver<'a> {
             interpreter,
             scopes: Default::default(),
             current_function: FunctionType::None,
-            current_class: ClassType::None
+            current_class: ClassType::None,
+            current_loop: LoopType::None,
+            class_inits: vec![HashMap::new()],
+            calls_super_init: false,
+            warnings: Vec::new(),
+            private_names: Vec::new(),
         }
     }
 
     pub fn begin_scope(&mut self) {
         self.scopes.borrow_mut().push(HashMap::new());
+        self.class_inits.push(HashMap::new());
     }
 
     pub fn end_scope(&mut self) {
         self.scopes.borrow_mut().pop();
+        self.class_inits.pop();
     }
 
     pub fn resolve<T: Resolvable>(&mut self, node: &T) -> LoxResult<()> {
         node.resolve(self)
     }
 
-    pub fn declare (&self, token: &Token) -> LoxResult<()> {
+    pub fn declare (&mut self, token: &Token) -> LoxResult<()> {
+        Self::check_not_private(token)?;
+        // a new binding hides any class of the same name from an outer scope
+        if let Some(names) = self.class_inits.last_mut() {
+            names.insert(token.lexeme.clone(), None);
+        }
         if self.scopes.borrow().is_empty() {
             return Ok(());
         }
@@ -93,6 +115,7 @@ impl<'a> Resolver<'a> {
     }
 
     pub fn resolve_local (&mut self, expr: &ExpressionType, token: &Token) -> LoxResult<()>{
+        Self::check_not_private(token)?;
         let scopes = self.scopes.borrow();
         for (idx, scope) in scopes.iter().enumerate().rev() {
             if scope.contains_key(&token.lexeme) {
@@ -103,9 +126,65 @@ impl<'a> Resolver<'a> {
         Ok(())
     }
 
+    // records that the class just declared under `name` in the current scope does or doesn't give its instances an init
+    pub fn declare_class_init(&mut self, name: &Token, has_init: bool) {
+        if let Some(names) = self.class_inits.last_mut() {
+            names.insert(name.lexeme.clone(), Some(has_init));
+        }
+    }
+
+    // whether `name`, looked up like a variable from the current scope, is a class whose instances get an init.
+    // classes that come from somewhere the resolver can't see, like another module, count as having none
+    pub fn class_has_init(&self, name: &Token) -> bool {
+        self.class_inits
+            .iter()
+            .rev()
+            .find_map(|names| names.get(&name.lexeme))
+            .copied()
+            .flatten()
+            .unwrap_or(false)
+    }
+
+    // imports and exports bind module-level names, so they can't appear inside blocks or functions
+    pub fn top_level(&self, keyword: &Token, what: &str) -> LoxResult<()> {
+        if !self.scopes.borrow().is_empty() || self.current_function != FunctionType::None {
+            return Err(LoxError::RuntimeError {
+                token: Some(keyword.clone()),
+                message: format!("{} are only allowed at the top level of a file", what),
+            });
+        }
+        Ok(())
+    }
+
+    // `obj.#name` has to sit in the body of the innermost class around it, and that class has to declare #name.
+    // a class nested in another one's method doesn't see the outer class's private members, same as at runtime
+    pub fn check_private(&self, name: &Token) -> LoxResult<()> {
+        if name.lexeme.starts_with('#') && !self.private_names.last().is_some_and(|names| names.contains(&name.lexeme)) {
+            return Err(LoxError::RuntimeError {
+                token: Some(name.clone()),
+                message: format!("Private member {} is not declared in the enclosing class", name.lexeme),
+            });
+        }
+        Ok(())
+    }
+
+    // `#names` are member names, they can't be variables, functions, parameters or classes, in a class body or not
+    fn check_not_private(token: &Token) -> LoxResult<()> {
+        if token.lexeme.starts_with('#') {
+            return Err(LoxError::RuntimeError {
+                token: Some(token.clone()),
+                message: format!("Private name {} can only be used as a class member, like this.{}", token.lexeme, token.lexeme),
+            });
+        }
+        Ok(())
+    }
+
     pub fn resolve_function (&mut self, func: &FunctionProps, func_type: FunctionType) -> LoxResult<()> {
         let enclosing_function = self.current_function.clone();
+        let enclosing_loop = self.current_loop.clone();
         self.current_function = func_type;
+        // a loop around the function body can't be broken out of from inside the function
+        self.current_loop = LoopType::None;
         self.begin_scope();
         for param in &func.params {
             self.declare(param)?;
@@ -114,6 +193,7 @@ impl<'a> Resolver<'a> {
         self.resolve(&*func.body)?;
         self.end_scope();
         self.current_function = enclosing_function;
+        self.current_loop = enclosing_loop;
         Ok(())
     }
 }
@@ -1,9 +1,12 @@
 use crate::token::{AtomicLiteral, Token, TokenType};
 use crate::lox_error::{LoxError, LoxResult};
+use std::iter::Peekable;
+use std::str::Chars;
 
-const _KEYWORDS: [&str; 16] = [
+const _KEYWORDS: [&str; 29] = [
     "and", "class", "else", "false", "fn", "for", "if", "null", "or", "print", "return", "super",
-    "this", "true", "let", "while",
+    "this", "true", "let", "while", "break", "continue", "try", "catch", "finally", "throw",
+    "import", "export", "as", "static", "is", "trait", "with",
 ];
 
 fn _is_keyword(input: &str) -> bool {
@@ -31,6 +34,19 @@ fn _match_keyword(input: &str) -> TokenType {
         "true" => TokenType::TRUE,
         "let" => TokenType::LET,
         "while" => TokenType::WHILE,
+        "break" => TokenType::BREAK,
+        "continue" => TokenType::CONTINUE,
+        "try" => TokenType::TRY,
+        "catch" => TokenType::CATCH,
+        "finally" => TokenType::FINALLY,
+        "throw" => TokenType::THROW,
+        "import" => TokenType::IMPORT,
+        "export" => TokenType::EXPORT,
+        "as" => TokenType::AS,
+        "static" => TokenType::STATIC,
+        "is" => TokenType::IS,
+        "trait" => TokenType::TRAIT,
+        "with" => TokenType::WITH,
         _ => TokenType::IDENTIFIER, // should not reach here
     }
 }
@@ -49,9 +65,163 @@ fn _is_alphanumeric(c: char) -> bool {
     return false;
 }
 
+fn string_token(value: &str, line: u32) -> Token {
+    Token::new(
+        TokenType::STRING,
+        value.to_string(),
+        line,
+        AtomicLiteral::String(value.to_string()),
+    )
+}
+
+fn scan_error(lexeme: &str, line: u32, message: &str) -> LoxError {
+    LoxError::ScanError {
+        token: Token::new(TokenType::STRING, lexeme.to_string(), line, AtomicLiteral::Nil),
+        message: message.to_string(),
+    }
+}
+
+// collects the source of a `${ ... }` up to its matching '}', skipping over braces inside nested strings
+fn interpolation_source(char_iter: &mut Peekable<Chars>, line: u32) -> LoxResult<String> {
+    let mut source = String::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    while let Some(c) = char_iter.next() {
+        match c {
+            '\0' => break,
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                source.push(c);
+                if let Some(escaped) = char_iter.next() {
+                    source.push(escaped);
+                }
+                continue;
+            }
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                if depth == 0 {
+                    return Ok(source);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        source.push(c);
+    }
+    Err(scan_error("${", line, "Unterminated interpolation"))
+}
+
+// scans the rest of a string literal after its opening quote. escapes are decoded here and
+// every `${expr}` is lowered into a concatenation, so `"a ${b} c"` yields the tokens of `("a " + (b) + " c")`
+fn scan_string(char_iter: &mut Peekable<Chars>, current_line: &mut u32) -> LoxResult<Vec<Token>> {
+    let start_line = *current_line;
+    let mut pieces: Vec<Token> = Vec::new();
+    let mut segment = String::new();
+    let mut interpolated = false;
+    let mut terminated = false;
+
+    while let Some(next_char) = char_iter.next() {
+        match next_char {
+            '"' => {
+                terminated = true;
+                break;
+            }
+            '\0' => break,
+            '\n' => {
+                *current_line += 1;
+                segment.push(next_char);
+            }
+            '\\' => {
+                let escaped = match char_iter.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('0') => '\0',
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('$') => '$',
+                    Some(other) if other != '\0' => {
+                        return Err(scan_error(
+                            &format!("\\{}", other),
+                            *current_line,
+                            &format!("Invalid escape sequence '\\{}'", other),
+                        ));
+                    }
+                    _ => break,
+                };
+                segment.push(escaped);
+            }
+            '$' if char_iter.peek() == Some(&'{') => {
+                char_iter.next();
+                let line = *current_line;
+                let source = interpolation_source(char_iter, line)?;
+                if source.trim().is_empty() {
+                    return Err(scan_error("${", line, "Empty interpolation"));
+                }
+                let mut inner = scan_from(&source, line)?;
+                inner.pop(); // the nested EOF
+                *current_line += source.matches('\n').count() as u32;
+
+                pieces.push(string_token(&segment, line));
+                pieces.push(Token::new(TokenType::PLUS, "+".to_string(), line, AtomicLiteral::Nil));
+                pieces.push(Token::new(TokenType::LEFTPAREN, "(".to_string(), line, AtomicLiteral::Nil));
+                pieces.extend(inner);
+                pieces.push(Token::new(TokenType::RIGHTPAREN, ")".to_string(), *current_line, AtomicLiteral::Nil));
+                pieces.push(Token::new(TokenType::PLUS, "+".to_string(), *current_line, AtomicLiteral::Nil));
+                segment.clear();
+                interpolated = true;
+            }
+            _ => segment.push(next_char),
+        }
+    }
+
+    if !terminated {
+        return Err(scan_error("\"", start_line, "Unterminated string"));
+    }
+
+    pieces.push(string_token(&segment, *current_line));
+    if interpolated {
+        pieces.insert(0, Token::new(TokenType::LEFTPAREN, "(".to_string(), start_line, AtomicLiteral::Nil));
+        pieces.push(Token::new(TokenType::RIGHTPAREN, ")".to_string(), *current_line, AtomicLiteral::Nil));
+    }
+    Ok(pieces)
+}
+
+// skips a /* ... */ comment whose opening has already been consumed, nested comments have to be closed too
+fn skip_block_comment(char_iter: &mut Peekable<Chars>, current_line: &mut u32) -> LoxResult<()> {
+    let start_line = *current_line;
+    let mut depth = 1;
+    while let Some(c) = char_iter.next() {
+        match c {
+            '\0' => break,
+            '\n' => *current_line += 1,
+            '/' if char_iter.peek() == Some(&'*') => {
+                char_iter.next();
+                depth += 1;
+            }
+            '*' if char_iter.peek() == Some(&'/') => {
+                char_iter.next();
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(LoxError::ScanError {
+        token: Token::new(TokenType::SLASH, "/*".to_string(), start_line, AtomicLiteral::Nil),
+        message: format!("Unterminated block comment starting at line {}", start_line),
+    })
+}
+
 pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
+    scan_from(input, 1)
+}
+
+fn scan_from(input: &str, first_line: u32) -> LoxResult<Vec<Token>> {
     let mut tokens: Vec<Token> = Vec::new();
-    let mut current_line = 1;
+    let mut current_line = first_line;
     let mut token_lexeme = String::new();
 
     let push_token = |tokens: &mut Vec<Token>, token: Token, token_lexeme: &mut String| {
@@ -86,6 +256,21 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                     &mut token_lexeme,
                 );
             }
+            '?' => {
+                let (tokentype, lexeme) = match char_iter.peek() {
+                    Some('?') => (TokenType::QUESTIONQUESTION, "??"),
+                    Some('.') => (TokenType::QUESTIONDOT, "?."),
+                    _ => (TokenType::QUESTION, "?"),
+                };
+                if lexeme.len() == 2 {
+                    char_iter.next();
+                }
+                push_token(
+                    &mut tokens,
+                    Token::new(tokentype, lexeme.to_string(), current_line, AtomicLiteral::Nil),
+                    &mut token_lexeme,
+                );
+            }
             '{' => {
                 push_token(
                     &mut tokens,
@@ -99,23 +284,61 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                 );
             }
             '%' => {
+                if let Some('=') = char_iter.peek() {
+                    char_iter.next();
+                    push_token(
+                        &mut tokens,
+                        Token::new(
+                            TokenType::MODULOEQUAL,
+                            "%=".to_string(),
+                            current_line,
+                            AtomicLiteral::Nil,
+                        ),
+                        &mut token_lexeme,
+                    );
+                } else {
+                    push_token(
+                        &mut tokens,
+                        Token::new(
+                            TokenType::MODULO,
+                            "%".to_string(),
+                            current_line,
+                            AtomicLiteral::Nil,
+                        ),
+                        &mut token_lexeme,
+                    );
+                }
+            }
+            '}' => {
                 push_token(
                     &mut tokens,
                     Token::new(
-                        TokenType::MODULO,
-                        "%".to_string(),
+                        TokenType::RIGHTBRACE,
+                        "}".to_string(),
                         current_line,
                         AtomicLiteral::Nil,
                     ),
                     &mut token_lexeme,
                 );
             }
-            '}' => {
+            '[' => {
                 push_token(
                     &mut tokens,
                     Token::new(
-                        TokenType::RIGHTBRACE,
-                        "}".to_string(),
+                        TokenType::LEFTBRACKET,
+                        "[".to_string(),
+                        current_line,
+                        AtomicLiteral::Nil,
+                    ),
+                    &mut token_lexeme,
+                );
+            }
+            ']' => {
+                push_token(
+                    &mut tokens,
+                    Token::new(
+                        TokenType::RIGHTBRACKET,
+                        "]".to_string(),
                         current_line,
                         AtomicLiteral::Nil,
                     ),
@@ -171,17 +394,43 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                 );
             }
             '*' => {
-                push_token(
-                    &mut tokens,
-                    Token::new(TokenType::STAR, "*".to_string(), current_line, AtomicLiteral::Nil),
-                    &mut token_lexeme,
-                );
+                if let Some('=') = char_iter.peek() {
+                    char_iter.next();
+                    push_token(
+                        &mut tokens,
+                        Token::new(
+                            TokenType::STAREQUAL,
+                            "*=".to_string(),
+                            current_line,
+                            AtomicLiteral::Nil,
+                        ),
+                        &mut token_lexeme,
+                    );
+                } else {
+                    push_token(
+                        &mut tokens,
+                        Token::new(TokenType::STAR, "*".to_string(), current_line, AtomicLiteral::Nil),
+                        &mut token_lexeme,
+                    );
+                }
             }
 
             // one or two character tokens
             '+' => {
                 if let Some(next_char) = char_iter.peek() {
-                    if next_char == &'+' {
+                    if next_char == &'=' {
+                        char_iter.next();
+                        push_token(
+                            &mut tokens,
+                            Token::new(
+                                TokenType::PLUSEQUAL,
+                                "+=".to_string(),
+                                current_line,
+                                AtomicLiteral::Nil,
+                            ),
+                            &mut token_lexeme,
+                        );
+                    } else if next_char == &'+' {
                         char_iter.next();
                         push_token(
                             &mut tokens,
@@ -209,7 +458,19 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
             }
             '-' => {
                 if let Some(next_char) = char_iter.peek() {
-                    if next_char == &'-' {
+                    if next_char == &'=' {
+                        char_iter.next();
+                        push_token(
+                            &mut tokens,
+                            Token::new(
+                                TokenType::MINUSEQUAL,
+                                "-=".to_string(),
+                                current_line,
+                                AtomicLiteral::Nil,
+                            ),
+                            &mut token_lexeme,
+                        );
+                    } else if next_char == &'-' {
                         char_iter.next();
                         push_token(
                             &mut tokens,
@@ -462,6 +723,21 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                             break;
                         }
                     }
+                } else if let Some('*') = char_iter.peek() {
+                    char_iter.next();
+                    skip_block_comment(&mut char_iter, &mut current_line)?;
+                } else if let Some('=') = char_iter.peek() {
+                    char_iter.next();
+                    push_token(
+                        &mut tokens,
+                        Token::new(
+                            TokenType::SLASHEQUAL,
+                            "/=".to_string(),
+                            current_line,
+                            AtomicLiteral::Nil,
+                        ),
+                        &mut token_lexeme,
+                    );
                 } else {
                     push_token(
                         &mut tokens,
@@ -476,38 +752,7 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                 }
             }
             '"' => {
-                while let Some(next_char) = char_iter.next() {
-                    if next_char == '"' {
-                        break;
-                    }
-                    if next_char == '\0' {
-                       return Err(
-                            LoxError::ScanError { 
-                                token: Token::new(
-                                    TokenType::EQUAL,
-                                    "\"".to_string(),
-                                    current_line,
-                                    AtomicLiteral::Nil,
-                                ),
-                                message: "Unterminated string".to_string(), 
-                            }
-                        )
-                    }
-                    if next_char == '\n' {
-                        current_line += 1;
-                    }
-                    token_lexeme.push(next_char);
-                }
-                push_token(
-                    &mut tokens,
-                    Token::new(
-                        TokenType::STRING,
-                        token_lexeme.clone(),
-                        current_line,
-                        AtomicLiteral::String(token_lexeme.clone()),
-                    ),
-                    &mut token_lexeme,
-                );
+                tokens.extend(scan_string(&mut char_iter, &mut current_line)?);
             }
             c if _is_number(c) => {
                 token_lexeme.push(c);
@@ -519,7 +764,30 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                         break;
                     }
                 }
-                let num_result = token_lexeme.parse::<i32>();
+                // a '.' only belongs to the number when a digit follows it, so `1.foo` still scans as a property access
+                let mut is_float = false;
+                if let Some('.') = char_iter.peek() {
+                    let mut lookahead = char_iter.clone();
+                    lookahead.next();
+                    if let Some(&c) = lookahead.peek() && _is_number(c) {
+                        is_float = true;
+                        token_lexeme.push('.');
+                        char_iter.next();
+                        while let Some(&next_char) = char_iter.peek() {
+                            if _is_number(next_char) {
+                                token_lexeme.push(next_char);
+                                char_iter.next();
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+                }
+                let num_result = if is_float {
+                    token_lexeme.parse::<f64>().map(AtomicLiteral::Float).map_err(|_| ())
+                } else {
+                    token_lexeme.parse::<i32>().map(AtomicLiteral::Number).map_err(|_| ())
+                };
                 match num_result {
                     Err(_) => {
                         return Err(
@@ -541,7 +809,7 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                                 TokenType::NUMBER,
                                 token_lexeme.clone(),
                                 current_line,
-                                AtomicLiteral::Number(num),
+                                num,
                             ),
                             &mut token_lexeme,
                         );
@@ -549,6 +817,35 @@ pub fn scanner(input: &str) -> LoxResult<Vec<Token>> {
                 }
             }
 
+            // `#name` is a private member name, scanned as one identifier
+            '#' => {
+                token_lexeme.push('#');
+                while let Some(&next_char) = char_iter.peek() {
+                    if _is_alphanumeric(next_char) {
+                        token_lexeme.push(next_char);
+                        char_iter.next();
+                    } else {
+                        break;
+                    }
+                }
+                if token_lexeme.len() == 1 {
+                    return Err(LoxError::ScanError {
+                        token: Token::new(TokenType::IDENTIFIER, token_lexeme.clone(), current_line, AtomicLiteral::Nil),
+                        message: "Expected a name after '#'".to_string(),
+                    });
+                }
+                push_token(
+                    &mut tokens,
+                    Token::new(
+                        TokenType::IDENTIFIER,
+                        token_lexeme.clone(),
+                        current_line,
+                        AtomicLiteral::Nil,
+                    ),
+                    &mut token_lexeme,
+                );
+            }
+
             c if _is_alphanumeric(c) => {
                 token_lexeme.push(c);
                 while let Some(&next_char) = char_iter.peek() {
@@ -1,9 +1,11 @@
 use crate::environment::Environment;
-use crate::expression::{ClassType, ExpressionType, FunctionType, is_truthy};
+use crate::expression::{ClassType, ExpressionType, FunctionType, LoopType, is_truthy};
 use crate::interpreter::Interpreter;
-use crate::lox_class::LoxClass;
+use crate::lox_class::{ClassMembers, LoxClass};
 use crate::lox_error::LoxError;
 use crate::lox_error::LoxResult;
+use crate::lox_module::LoxModule;
+use crate::lox_trait::LoxTrait;
 use crate::loxfuncs::LoxFunction;
 use crate::resolver::{Resolvable, Resolver};
 use crate::token::Literal;
@@ -24,20 +26,71 @@ pub enum StatementType {
     Function(FunctionProps),
     WhileStatement(WhileProps),
     ReturnStatement(ReturnProps),
-    ClassStatement(ClassProps),
+    ClassStatement(Box<ClassProps>),
+    BreakStatement(Token),
+    ContinueStatement(Token),
+    ThrowStatement(ThrowProps),
+    TryStatement(TryProps),
+    ImportStatement(ImportProps),
+    ExportStatement(ExportProps),
+    TraitStatement(TraitProps),
 }
 #[derive(Clone)]
 pub struct ClassProps {
     pub name: Token,
     pub methods: Vec<StatementType>,
+    pub static_methods: Vec<StatementType>,
+    pub static_fields: Vec<LetExpressionProps>,
+    pub getters: Vec<StatementType>,
+    pub setters: Vec<StatementType>,
+    // methods declared without a body, a subclass or trait has to supply them
+    pub abstract_methods: Vec<Token>,
+    // the class body's `let` fields, gathered by the parser into a method that sets them on `this`
+    pub field_initializer: Option<FunctionProps>,
+    // the `#name` members the class body declares
+    pub private_names: Vec<String>,
+    // the traits listed after `with`, in the order they were written
+    pub traits: Vec<ExpressionType>,
     pub superclass: Option<ExpressionType>,
 }
 #[derive(Clone)]
+pub struct TraitProps {
+    pub name: Token,
+    pub methods: Vec<StatementType>,
+    // methods a class including the trait has to define
+    pub abstract_methods: Vec<Token>,
+}
+#[derive(Clone)]
 pub struct ReturnProps {
     pub _keyword: Token,
     pub value: Option<ExpressionType>,
 }
 #[derive(Clone)]
+pub struct ThrowProps {
+    pub keyword: Token,
+    pub value: ExpressionType,
+}
+#[derive(Clone)]
+pub struct TryProps {
+    pub body: Box<StatementType>,
+    // the name the caught error is bound to, always set together with catch_body
+    pub catch_name: Option<Token>,
+    pub catch_body: Option<Box<StatementType>>,
+    pub finally_body: Option<Box<StatementType>>,
+}
+#[derive(Clone)]
+pub struct ImportProps {
+    pub keyword: Token,
+    // the path string, relative to the file doing the import
+    pub path: Token,
+    pub name: Token,
+}
+#[derive(Clone)]
+pub struct ExportProps {
+    pub keyword: Token,
+    pub declaration: Box<StatementType>,
+}
+#[derive(Clone)]
 pub struct FunctionProps {
     pub name: Token,
     pub params: Vec<Token>,
@@ -47,6 +100,8 @@ pub struct FunctionProps {
 pub struct WhileProps {
     pub condition: ExpressionType,
     pub statement: Box<StatementType>,
+    // the increment of a desugared for loop, it runs after every iteration including ones cut short by continue
+    pub increment: Option<ExpressionType>,
 }
 #[derive(Clone)]
 pub struct IfProps {
@@ -109,6 +164,12 @@ impl Resolvable for StatementType {
                         message: "Cannot return from top-level code.".to_string(),
                     });
                 }
+                if resolver.current_function == FunctionType::FieldInitializer {
+                    return Err(LoxError::RuntimeError {
+                        token: Some(statement._keyword.clone()),
+                        message: "Cannot return from a field default".to_string(),
+                    });
+                }
                 if let Some(value) = &statement.value {
                     if resolver.current_function == FunctionType::Initializer {
                         return Err(LoxError::RuntimeError {
@@ -122,14 +183,71 @@ impl Resolvable for StatementType {
 
             StatementType::WhileStatement(statement) => {
                 resolver.resolve(&statement.condition)?;
+                let enclosing_loop = resolver.current_loop.clone();
+                resolver.current_loop = LoopType::Loop;
                 resolver.resolve(&*statement.statement)?;
+                resolver.current_loop = enclosing_loop;
+                if let Some(increment) = &statement.increment {
+                    resolver.resolve(increment)?;
+                }
+            }
+            StatementType::ThrowStatement(statement) => {
+                resolver.resolve(&statement.value)?;
+            }
+            StatementType::TryStatement(statement) => {
+                resolver.resolve(&*statement.body)?;
+                if let (Some(name), Some(catch_body)) = (&statement.catch_name, &statement.catch_body) {
+                    resolver.begin_scope();
+                    resolver.declare(name)?;
+                    resolver.define(name);
+                    resolver.resolve(&**catch_body)?;
+                    resolver.end_scope();
+                }
+                if let Some(finally_body) = &statement.finally_body {
+                    resolver.resolve(&**finally_body)?;
+                }
+            }
+            StatementType::ImportStatement(statement) => {
+                resolver.top_level(&statement.keyword, "Imports")?;
+                resolver.declare(&statement.name)?;
+                resolver.define(&statement.name);
+            }
+            StatementType::ExportStatement(statement) => {
+                resolver.top_level(&statement.keyword, "Exports")?;
+                resolver.resolve(&*statement.declaration)?;
+            }
+            StatementType::BreakStatement(keyword) => {
+                if resolver.current_loop == LoopType::None {
+                    return Err(LoxError::RuntimeError {
+                        token: Some(keyword.clone()),
+                        message: "Cannot break outside of a loop.".to_string(),
+                    });
+                }
+            }
+            StatementType::ContinueStatement(keyword) => {
+                if resolver.current_loop == LoopType::None {
+                    return Err(LoxError::RuntimeError {
+                        token: Some(keyword.clone()),
+                        message: "Cannot continue outside of a loop.".to_string(),
+                    });
+                }
             }
             StatementType::ClassStatement(class_prop) => {
                 let enclosing_class = resolver.current_class.clone();
                 resolver.current_class = ClassType::Class;
+                resolver.private_names.push(class_prop.private_names.iter().cloned().collect());
 
+                // the superclass is looked up before the class's own name shadows it
+                let parent_has_init = match &class_prop.superclass {
+                    Some(ExpressionType::Variable(superclass)) => resolver.class_has_init(superclass),
+                    _ => false,
+                };
+                let has_init = parent_has_init || class_prop.methods.iter().any(|method| {
+                    matches!(method, StatementType::Function(func) if func.name.lexeme == "init")
+                });
                 resolver.declare(&class_prop.name)?;
                 resolver.define(&class_prop.name);
+                resolver.declare_class_init(&class_prop.name, has_init);
 
                 if let Some(superclass) = &class_prop.superclass {
                     resolver.current_class = ClassType::SubClass;
@@ -141,6 +259,9 @@ impl Resolvable for StatementType {
                         scope.insert("super".to_string(), true);
                     }
                 }
+                for included in &class_prop.traits {
+                    resolver.resolve(included)?;
+                }
 
                 resolver.begin_scope();
                 {
@@ -155,10 +276,25 @@ impl Resolvable for StatementType {
                     if let StatementType::Function(func) = method {
                         if func.name.lexeme == "init" {
                             declaration = FunctionType::Initializer;
+                            resolver.calls_super_init = false;
                         }
                         resolver.resolve_function(func, declaration)?;
+                        if func.name.lexeme == "init" && parent_has_init && !resolver.calls_super_init {
+                            resolver.warnings.push(format!(
+                                "[Line {}] Warning: {}.init never calls super.init",
+                                func.name.line, class_prop.name.lexeme
+                            ));
+                        }
                     }
                 }
+                for method in class_prop.static_methods.iter().chain(&class_prop.getters).chain(&class_prop.setters) {
+                    if let StatementType::Function(func) = method {
+                        resolver.resolve_function(func, FunctionType::Method)?;
+                    }
+                }
+                if let Some(func) = &class_prop.field_initializer {
+                    resolver.resolve_function(func, FunctionType::FieldInitializer)?;
+                }
 
                 resolver.end_scope(); 
 
@@ -166,6 +302,35 @@ impl Resolvable for StatementType {
                     resolver.end_scope();
                 }
 
+                // static field initializers run once the class exists, in the scope the class is declared in
+                for field in &class_prop.static_fields {
+                    resolver.resolve(&*field.initializer)?;
+                }
+
+                resolver.private_names.pop();
+                resolver.current_class = enclosing_class;
+            }
+            StatementType::TraitStatement(trait_prop) => {
+                let enclosing_class = resolver.current_class.clone();
+                resolver.current_class = ClassType::Trait;
+
+                resolver.declare(&trait_prop.name)?;
+                resolver.define(&trait_prop.name);
+
+                resolver.begin_scope();
+                {
+                    let mut scopes = resolver.scopes.borrow_mut();
+                    if let Some(scope) = scopes.last_mut() {
+                        scope.insert("this".to_string(), true);
+                    }
+                }
+                for method in &trait_prop.methods {
+                    if let StatementType::Function(func) = method {
+                        resolver.resolve_function(func, FunctionType::Method)?;
+                    }
+                }
+                resolver.end_scope();
+
                 resolver.current_class = enclosing_class;
             } // _ => {}
         }
@@ -174,6 +339,20 @@ impl Resolvable for StatementType {
 }
 
 impl StatementType {
+    // the name an exported declaration binds, None for anything that isn't exported
+    pub fn exported_name(&self) -> Option<&Token> {
+        match self {
+            StatementType::ExportStatement(prop) => match &*prop.declaration {
+                StatementType::LetStatement(declaration) => Some(&declaration.name),
+                StatementType::Function(declaration) => Some(&declaration.name),
+                StatementType::ClassStatement(declaration) => Some(&declaration.name),
+                StatementType::TraitStatement(declaration) => Some(&declaration.name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub fn evaluate(&self, interpreter: &mut Interpreter) -> LoxResult<()> {
         match self {
             StatementType::ExpressionStatement(value) => {
@@ -182,7 +361,7 @@ impl StatementType {
             }
             StatementType::PrintStatement(expr) => {
                 let output = expr.evaluate(interpreter)?;
-                println!("{}", output);
+                println!("{}", interpreter.stringify(&output)?);
                 Ok(())
             }
             StatementType::LetStatement(expr) => match *expr.initializer {
@@ -215,6 +394,25 @@ impl StatementType {
                 )?;
                 Ok(())
             }
+            StatementType::ThrowStatement(prop) => {
+                let value = prop.value.evaluate(interpreter)?;
+                Err(LoxError::Thrown {
+                    line: prop.keyword.line,
+                    value,
+                })
+            }
+            StatementType::TryStatement(prop) => Self::evaluate_try(prop, interpreter),
+            StatementType::ImportStatement(prop) => {
+                let module = LoxModule::load(interpreter, &prop.path)?;
+                interpreter
+                    .env
+                    .borrow_mut()
+                    .define(prop.name.clone(), Literal::Module(module))?;
+                Ok(())
+            }
+            StatementType::ExportStatement(prop) => prop.declaration.evaluate(interpreter),
+            StatementType::BreakStatement(_) => Err(LoxError::Break),
+            StatementType::ContinueStatement(_) => Err(LoxError::Continue),
             StatementType::ReturnStatement(prop) => {
                 let value = match &prop.value {
                     Some(expr) => expr.evaluate(interpreter)?,
@@ -223,43 +421,27 @@ impl StatementType {
                 Err(LoxError::ReturnValue(value))
             }
             StatementType::ClassStatement(class_prop) => {
-                let mut superclass = None;
-                if let Some(result) = &class_prop.superclass {
-                    superclass = Some(Rc::new(result.evaluate(interpreter)?.as_class()?));
+                // the class body, and every function it creates, can reach the class's private members
+                let declaration = Rc::new(class_prop.name.clone());
+                let previous = interpreter.current_class.replace(declaration.clone());
+                let result = Self::evaluate_class(class_prop, declaration, interpreter);
+                interpreter.current_class = previous;
+                result
+            }
+            StatementType::TraitStatement(trait_prop) => {
+                let mut methods = HashMap::new();
+                for method in &trait_prop.methods {
+                    if let StatementType::Function(func) = method {
+                        let function = LoxFunction::new(Rc::new(func), interpreter, false);
+                        methods.insert(func.name.lexeme.clone(), function);
+                    }
                 }
+                let abstract_methods = trait_prop.abstract_methods.iter().map(|name| name.lexeme.clone()).collect();
+                let lox_trait = LoxTrait::new(trait_prop.name.clone(), methods, abstract_methods);
                 interpreter
                     .env
                     .borrow_mut()
-                    .define(class_prop.name.clone(), Literal::Basic(AtomicLiteral::Nil))?;
-                let previous = Rc::clone(&interpreter.env);
-                if let Some(superclass) = superclass.clone() {
-                    let current = Environment::new(Some(previous.clone()));
-                    interpreter.env = Rc::new(RefCell::new(current));
-                    interpreter.env.borrow_mut().define(
-                        Token::new(TokenType::SUPER, "super".to_string(), 0, AtomicLiteral::Nil),
-                        Literal::LoxCallable(superclass),
-                    )?;
-                }
-                let mut methods = HashMap::new();
-                for method in &class_prop.methods {
-                    match method {
-                        StatementType::Function(func) => {
-                            let is_initializer = func.name.lexeme == "init".to_string();
-                            let function =
-                                LoxFunction::new(Rc::new(func), interpreter, is_initializer);
-                            methods.insert(func.name.lexeme.clone(), function)
-                        }
-                        _ => unreachable!(),
-                    };
-                }
-                let class = LoxClass::new(class_prop.name.clone(), methods, superclass);
-                if class_prop.superclass.is_some() {
-                    interpreter.env = previous;
-                }
-                interpreter.env.borrow_mut().assign(
-                    class_prop.name.clone(),
-                    Literal::LoxCallable(Rc::new(class)),
-                )?;
+                    .define(trait_prop.name.clone(), Literal::Trait(Rc::new(lox_trait)))?;
                 Ok(())
             } // _ => Ok(()),
         }
@@ -275,12 +457,17 @@ impl StatementType {
             previous.clone(),
         ))));
 
+        // break, continue and return unwind through blocks as errors, so the scope has to be restored either way
+        let mut result = Ok(());
         for statement in statements {
-            statement.evaluate(interpreter)?;
+            result = statement.evaluate(interpreter);
+            if result.is_err() {
+                break;
+            }
         }
 
         interpreter.env = previous;
-        Ok(())
+        result
     }
 
     pub fn evaluate_func_block(
@@ -298,6 +485,114 @@ impl StatementType {
         result
     }
 
+    fn evaluate_class(class_prop: &ClassProps, declaration: Rc<Token>, interpreter: &mut Interpreter) -> LoxResult<()> {
+        let mut superclass = None;
+        if let Some(result @ ExpressionType::Variable(name)) = &class_prop.superclass {
+            superclass = Some(Rc::new(result.evaluate(interpreter)?.as_class(name)?));
+        }
+        interpreter
+            .env
+            .borrow_mut()
+            .define(class_prop.name.clone(), Literal::Basic(AtomicLiteral::Nil))?;
+        let previous = Rc::clone(&interpreter.env);
+        if let Some(superclass) = superclass.clone() {
+            let current = Environment::new(Some(previous.clone()));
+            interpreter.env = Rc::new(RefCell::new(current));
+            interpreter.env.borrow_mut().define(
+                Token::new(TokenType::SUPER, "super".to_string(), 0, AtomicLiteral::Nil),
+                Literal::LoxCallable(superclass),
+            )?;
+        }
+        let mut members = ClassMembers::default();
+        for method in &class_prop.methods {
+            if let StatementType::Function(func) = method {
+                let is_initializer = func.name.lexeme == "init";
+                let function = LoxFunction::new(Rc::new(func), interpreter, is_initializer);
+                members.methods.insert(func.name.lexeme.clone(), function);
+            }
+        }
+        // own methods win over trait methods, and two traits may only share a method the class overrides
+        let mut from_trait: HashMap<String, String> = HashMap::new();
+        for included in &class_prop.traits {
+            let ExpressionType::Variable(token) = included else {
+                continue;
+            };
+            let value = included.evaluate(interpreter)?;
+            let Literal::Trait(lox_trait) = &value else {
+                return Err(LoxError::RuntimeError {
+                    token: Some(token.clone()),
+                    message: format!("{} is not a trait", value),
+                });
+            };
+            for (name, method) in lox_trait.methods() {
+                if members.methods.contains_key(name) && !from_trait.contains_key(name) {
+                    continue;
+                }
+                if let Some(other) = from_trait.get(name) {
+                    return Err(LoxError::RuntimeError {
+                        token: Some(class_prop.name.clone()),
+                        message: format!(
+                            "Class {} gets {} from both {} and {}, define it in the class to resolve the conflict",
+                            class_prop.name.lexeme, name, other, token.lexeme
+                        ),
+                    });
+                }
+                from_trait.insert(name.clone(), token.lexeme.clone());
+                members.methods.insert(name.clone(), method.clone());
+            }
+            members.abstract_methods.extend(lox_trait.abstract_methods().iter().cloned());
+        }
+        members.abstract_methods.extend(class_prop.abstract_methods.iter().map(|name| name.lexeme.clone()));
+        members.private_names.extend(class_prop.private_names.iter().cloned());
+        if let Some(func) = &class_prop.field_initializer {
+            members.field_initializer = Some(LoxFunction::new(Rc::new(func), interpreter, false));
+        }
+        let tables = [
+            (&class_prop.static_methods, &mut members.static_methods),
+            (&class_prop.getters, &mut members.getters),
+            (&class_prop.setters, &mut members.setters),
+        ];
+        for (declarations, table) in tables {
+            for declaration in declarations {
+                if let StatementType::Function(func) = declaration {
+                    let function = LoxFunction::new(Rc::new(func), interpreter, false);
+                    table.insert(func.name.lexeme.clone(), function);
+                }
+            }
+        }
+        let class = LoxClass::new(declaration, members, superclass);
+        if class_prop.superclass.is_some() {
+            interpreter.env = previous;
+        }
+        interpreter.env.borrow_mut().assign(
+            class_prop.name.clone(),
+            Literal::LoxCallable(Rc::new(class.clone())),
+        )?;
+        for field in &class_prop.static_fields {
+            let value = field.initializer.evaluate(interpreter)?;
+            class.set(&field.name, value);
+        }
+        Ok(())
+    }
+
+    pub fn evaluate_try(tried: &TryProps, interpreter: &mut Interpreter) -> LoxResult<()> {
+        let result = match (tried.body.evaluate(interpreter), &tried.catch_name, &tried.catch_body) {
+            // return, break and continue travel as errors too but are never caught
+            (Err(error @ (LoxError::RuntimeError { .. } | LoxError::Thrown { .. } | LoxError::Constructing { .. })), Some(name), Some(catch_body)) => {
+                let mut scope = Environment::new(Some(interpreter.env.clone()));
+                scope.define(name.clone(), interpreter.error_value(error))?;
+                Self::evaluate_func_block(catch_body, Rc::new(RefCell::new(scope)), interpreter)
+            }
+            (result, _, _) => result,
+        };
+
+        // an error raised by the finally block replaces whatever the try or catch block produced
+        if let Some(finally_body) = &tried.finally_body {
+            finally_body.evaluate(interpreter)?;
+        }
+        result
+    }
+
     pub fn evaluate_if(ifinput: &IfProps, interpreter: &mut Interpreter) -> LoxResult<()> {
         let comparison = ifinput.comparison.evaluate(interpreter)?;
 
@@ -314,7 +609,14 @@ impl StatementType {
             let cond = wild.condition.evaluate(interpreter)?;
             is_truthy(&cond)
         } {
-            wild.statement.evaluate(interpreter)?;
+            match wild.statement.evaluate(interpreter) {
+                Ok(()) | Err(LoxError::Continue) => {}
+                Err(LoxError::Break) => break,
+                Err(e) => return Err(e),
+            }
+            if let Some(increment) = &wild.increment {
+                increment.evaluate(interpreter)?;
+            }
         }
         Ok(())
     }
@@ -1,13 +1,16 @@
-use std::{fmt, rc::Rc};
+use std::{cell::RefCell, fmt, hash::{Hash, Hasher}, rc::Rc};
 use crate::lox_error::{LoxError, LoxResult};
 use crate::lox_instance::LoxInstance;
 use crate::callable::Callable;
 use crate::lox_class::LoxClass;
+use crate::lox_map::LoxMap;
+use crate::lox_module::LoxModule;
+use crate::lox_trait::LoxTrait;
 
 #[derive(Debug,Clone,PartialEq, Eq, Hash)]
 pub enum TokenType {
     // single char tokens
-    LEFTPAREN, RIGHTPAREN, LEFTBRACE, RIGHTBRACE,
+    LEFTPAREN, RIGHTPAREN, LEFTBRACE, RIGHTBRACE, LEFTBRACKET, RIGHTBRACKET,
     COMMA, DOT, MINUS, PLUS, SEMICOLON, SLASH, STAR,
 
     // One or two character tokens. 
@@ -16,6 +19,8 @@ pub enum TokenType {
     EQUAL, EQUALEQUAL,
     GREATER, GREATEREQUAL,
     LESS, LESSEQUAL,MODULO,DECREMENTOR,INCREMENTOR,
+    PLUSEQUAL, MINUSEQUAL, STAREQUAL, SLASHEQUAL, MODULOEQUAL,
+    QUESTION, QUESTIONQUESTION, QUESTIONDOT,
 
     // Literals.
     IDENTIFIER, STRING, NUMBER,
@@ -23,6 +28,7 @@ pub enum TokenType {
     // Keywords.
     AND, CLASS, ELSE, FALSE, FUNCTION, FOR, IF, NIL, OR,
     PRINT, RETURN, SUPER, THIS, TRUE, LET, WHILE, COLON,
+    BREAK, CONTINUE, TRY, CATCH, FINALLY, THROW, IMPORT, EXPORT, AS, STATIC, IS, TRAIT, WITH,
 
     EOF
 }
@@ -45,7 +51,16 @@ impl fmt::Display for TokenType {
             TokenType::LESSEQUAL => "<=",
             TokenType::DECREMENTOR => "--",
             TokenType::INCREMENTOR => "++",
+            TokenType::PLUSEQUAL => "+=",
+            TokenType::MINUSEQUAL => "-=",
+            TokenType::STAREQUAL => "*=",
+            TokenType::SLASHEQUAL => "/=",
+            TokenType::MODULOEQUAL => "%=",
             TokenType::COLON => ":",
+            TokenType::QUESTION => "?",
+            TokenType::QUESTIONQUESTION => "??",
+            TokenType::QUESTIONDOT => "?.",
+            TokenType::IS => "is",
             _ => return write!(f, "{:?}", self), // fallback for non-operators
         };
 
@@ -53,33 +68,119 @@ impl fmt::Display for TokenType {
     }
 }
 
-#[derive(Clone,PartialEq, Eq, Hash,Debug)]
+#[derive(Clone,Debug)]
 #[allow(dead_code)]
 
 pub enum AtomicLiteral {
     String(String),
     Number(i32),
+    Float(f64),
     Bool(bool),
     Nil,
 }
+
+// floats are compared and hashed by their bits so that literals can still be
+// used as keys, numeric equality between values lives in `is_equal`
+impl PartialEq for AtomicLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AtomicLiteral::String(a), AtomicLiteral::String(b)) => a == b,
+            (AtomicLiteral::Number(a), AtomicLiteral::Number(b)) => a == b,
+            (AtomicLiteral::Float(a), AtomicLiteral::Float(b)) => a.to_bits() == b.to_bits(),
+            (AtomicLiteral::Bool(a), AtomicLiteral::Bool(b)) => a == b,
+            (AtomicLiteral::Nil, AtomicLiteral::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AtomicLiteral {}
+
+impl Hash for AtomicLiteral {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            AtomicLiteral::String(s) => s.hash(state),
+            AtomicLiteral::Number(n) => n.hash(state),
+            AtomicLiteral::Float(n) => n.to_bits().hash(state),
+            AtomicLiteral::Bool(b) => b.hash(state),
+            AtomicLiteral::Nil => {}
+        }
+    }
+}
+
+pub fn format_float(n: f64) -> String {
+    if n.is_finite() && n.fract() == 0.0 {
+        format!("{:.1}", n)
+    } else {
+        format!("{}", n)
+    }
+}
 #[derive(Clone)]
 pub enum Literal {
     Basic(AtomicLiteral),
     LoxCallable(Rc<dyn Callable>),
     Instance(LoxInstance),
+    List(Rc<RefCell<Vec<Literal>>>),
+    Map(Rc<RefCell<LoxMap>>),
+    Module(Rc<LoxModule>),
+    Trait(Rc<LoxTrait>),
 }
 
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_nested(f, &mut Vec::new())
+    }
+}
+
+impl Literal {
+    // `printing` holds the lists and maps currently being written, one that contains itself shows up as [...] or {...}
+    fn write_nested(&self, f: &mut fmt::Formatter<'_>, printing: &mut Vec<*const ()>) -> fmt::Result {
         match self {
             Literal::Basic(atom) => match atom {
                 AtomicLiteral::String(s) => write!(f, "{}", s),
                 AtomicLiteral::Number(n) => write!(f, "{}", n),
+                AtomicLiteral::Float(n) => write!(f, "{}", format_float(*n)),
                 AtomicLiteral::Bool(b) => write!(f, "{}", b),
                 AtomicLiteral::Nil => write!(f, "nil"),
             },
-            Literal::LoxCallable(_) => write!(f, "<fn>"),
-            _ => write!(f, "<instance>")
+            Literal::LoxCallable(callable) => write!(f, "{}", callable.describe()),
+            Literal::List(items) => {
+                let id = Rc::as_ptr(items) as *const ();
+                if printing.contains(&id) {
+                    return write!(f, "[...]");
+                }
+                printing.push(id);
+                write!(f, "[")?;
+                for (i, item) in items.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write_nested(f, printing)?;
+                }
+                printing.pop();
+                write!(f, "]")
+            }
+            Literal::Map(map) => {
+                let id = Rc::as_ptr(map) as *const ();
+                if printing.contains(&id) {
+                    return write!(f, "{{...}}");
+                }
+                printing.push(id);
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().entries().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", Literal::Basic(key.clone()))?;
+                    value.write_nested(f, printing)?;
+                }
+                printing.pop();
+                write!(f, "}}")
+            }
+            Literal::Module(module) => write!(f, "<module {}>", module.name()),
+            Literal::Trait(tr) => write!(f, "<trait {}>", tr.name()),
+            Literal::Instance(instance) => write!(f, "<{} instance>", instance.class_name()),
         }
     }
 }
@@ -100,6 +201,7 @@ impl Token {
                 match value {
                     AtomicLiteral::String(value ) => return value,
                     AtomicLiteral::Number(value) => return value.to_string(),
+                    AtomicLiteral::Float(value) => return format_float(value),
                     AtomicLiteral::Bool(value) => return value.to_string(),
                     _ => return String::from("not a good thing")
                 }
@@ -111,7 +213,7 @@ impl Token {
     }
     pub fn new(tokentype: TokenType, lexeme: String, line: u32, literal:AtomicLiteral) -> Self {
         match literal {
-            AtomicLiteral::String(_) | AtomicLiteral::Number(_) | AtomicLiteral::Bool(_) => Token {
+            AtomicLiteral::String(_) | AtomicLiteral::Number(_) | AtomicLiteral::Float(_) | AtomicLiteral::Bool(_) => Token {
                 tokentype,
                 lexeme,
                 literal: Some(literal),
@@ -128,23 +230,19 @@ impl Token {
 }
 
 impl Literal {
-    pub fn as_class (&self) -> LoxResult<LoxClass> {
-        match self {
-            Literal::LoxCallable(v) => {
-                v.as_any().downcast_ref::<LoxClass>().ok_or_else(|| LoxError::RuntimeError { 
-                    token: None, 
-                    message: "Target is not a class.".to_string() 
-                }).cloned()
-            },
-            _ => Err(LoxError::RuntimeError { token: None, message: "not a class".to_string() })
-        }
+    // `token` is where the error points when the value turns out not to be a class
+    pub fn as_class (&self, token: &Token) -> LoxResult<LoxClass> {
+        self.class_ref().cloned().ok_or_else(|| LoxError::RuntimeError {
+            token: Some(token.clone()),
+            message: format!("{} is not a class", self),
+        })
     }
-    pub fn as_instance (&self) -> LoxResult<LoxInstance> {
+
+    // the class behind a callable, borrowed instead of cloned like as_class does
+    pub fn class_ref(&self) -> Option<&LoxClass> {
         match self {
-            Literal::Instance(v) => {
-                Ok(v.clone())
-            },
-            _ => Err(LoxError::RuntimeError { token: None, message: "not a instance".to_string() })
+            Literal::LoxCallable(callable) => callable.as_any().downcast_ref::<LoxClass>(),
+            _ => None,
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,95 @@
+// abstract methods and the missing super.init warning
+mod common;
+
+use common::{assert_error, assert_prints, run, write_script};
+
+#[test]
+fn implemented_abstract_methods_can_be_called() {
+    assert_prints(
+        "implemented",
+        "class Shape { area(); describe() { return \"area \" + this.area(); } }
+class Square : Shape { init(s) { this.s = s; } area() { return this.s * this.s; } }
+print Square(3).describe();
+",
+        &["area 9"],
+    );
+}
+
+#[test]
+fn instantiating_with_missing_methods_lists_them() {
+    assert_error(
+        "missing",
+        "class Shape { area(); perimeter(); name(); }\nclass Line : Shape { area() { return 0; } }\nLine();\n",
+        &[],
+        "[Line 3] [at )] Cannot instantiate abstract class Line, missing: name, perimeter",
+    );
+}
+
+#[test]
+fn traits_can_require_methods() {
+    assert_error(
+        "trait_requires",
+        "trait Comparable { compare(other); }\nclass Version with Comparable {}\nVersion();\n",
+        &[],
+        "[Line 3] [at )] Cannot instantiate abstract class Version, missing: compare",
+    );
+    assert_prints(
+        "trait_satisfied",
+        "trait Comparable { compare(other); less(o) { return this.compare(o) < 0; } }
+class V with Comparable { init(n) { this.n = n; } compare(o) { return this.n - o.n; } }
+print V(1).less(V(2));
+",
+        &["true"],
+    );
+}
+
+#[test]
+fn init_without_super_init_warns_before_running() {
+    let output = run(
+        "super_init_warning",
+        "class P { init() { this.x = 1; } }
+class Q : P { init() { this.y = 2; } }
+class R : P { init() { super.init(); } }
+class S : Q {}
+class T : S { init() {} }
+print \"ran\";
+",
+    );
+    assert_eq!(output.lines(), ["ran"]);
+    assert_eq!(
+        output.stderr,
+        "[Line 2] Warning: Q.init never calls super.init\n[Line 5] Warning: T.init never calls super.init"
+    );
+}
+
+#[test]
+fn warning_follows_the_class_the_name_refers_to() {
+    let output = run(
+        "super_init_scopes",
+        "class P { init() { this.x = 1; } }
+fn local() {
+  class P {}
+  class A : P { init() {} }
+}
+fn shadowed() {
+  let P = 1;
+  {
+    class B : P { init() {} }
+  }
+}
+class C : P { init() {} }
+",
+    );
+    assert_eq!(output.stderr, "[Line 12] Warning: C.init never calls super.init");
+}
+
+#[test]
+fn classes_in_other_modules_do_not_change_warnings() {
+    write_script("warning_lib", "export class P { init() { this.x = 1; } }\nclass Q : P { init() {} }\n");
+    let output = run(
+        "warning_main",
+        "import \"warning_lib.lox\" as lib;\nclass P {}\nclass A : P { init() {} }\nprint \"ran\";\n",
+    );
+    assert_eq!(output.lines(), ["ran"]);
+    assert_eq!(output.stderr, "In warning_lib.lox: [Line 2] Warning: Q.init never calls super.init");
+}
@@ -0,0 +1,55 @@
+// helpers shared by the integration tests, each test file only uses some of them
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    pub fn lines(&self) -> Vec<&str> {
+        self.stdout.lines().collect()
+    }
+}
+
+// writes the script to a temp file and runs the interpreter binary on it, like `interpreter script.lox`
+pub fn run(name: &str, source: &str) -> Output {
+    run_file(write_script(name, source))
+}
+
+// every script of a test run lands in the same directory, so one can import another as "name.lox"
+pub fn write_script(name: &str, source: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lox-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir for test scripts");
+    let path = dir.join(format!("{}.lox", name));
+    std::fs::write(&path, source).expect("test script written");
+    path
+}
+
+pub fn run_file(path: PathBuf) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter"))
+        .arg(&path)
+        .output()
+        .expect("interpreter binary runs");
+    Output {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim_end().to_string(),
+    }
+}
+
+// the script has to run cleanly and print exactly these lines
+pub fn assert_prints(name: &str, source: &str, expected: &[&str]) {
+    let output = run(name, source);
+    assert_eq!(output.stderr, "", "{} wrote to stderr", name);
+    assert_eq!(output.lines(), expected, "{} printed something else", name);
+}
+
+// the script has to stop with this error, after printing these lines
+pub fn assert_error(name: &str, source: &str, printed: &[&str], error: &str) {
+    let output = run(name, source);
+    assert_eq!(output.lines(), printed, "{} printed something else", name);
+    assert_eq!(output.stderr, error, "{} failed with a different error", name);
+}
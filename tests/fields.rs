@@ -0,0 +1,121 @@
+// `let` fields in a class body and their defaults
+mod common;
+
+use common::{assert_error, assert_prints};
+
+#[test]
+fn defaults_are_set_per_instance() {
+    assert_prints(
+        "fields_defaults",
+        "class Counter {
+  let count = 0;
+  let history = [0];
+}
+let first = Counter();
+let second = Counter();
+first.history[0] = 99;
+first.count = 3;
+print second.count;
+print second.history[0];
+",
+        &["0", "0"],
+    );
+}
+
+#[test]
+fn subclass_defaults_override_and_run_before_init() {
+    assert_prints(
+        "fields_subclass",
+        "class Counter {
+  let count = 0;
+  let step = 1;
+}
+class FastCounter : Counter {
+  let step = 10;
+  let label = \"step \" + this.step;
+  init(start) { this.count = start; }
+}
+let fast = FastCounter(5);
+print fast.count;
+print fast.label;
+print fields(fast);
+",
+        &["5", "step 10", "[count, label, step]"],
+    );
+}
+
+#[test]
+fn default_next_to_a_getter_is_not_a_read_only_property() {
+    assert_prints(
+        "fields_getter",
+        "class Temp {
+  let v = 2;
+  get v { return \"getter\"; }
+}
+print Temp().v;
+",
+        &["getter"],
+    );
+}
+
+#[test]
+fn default_does_not_call_the_setter() {
+    assert_prints(
+        "fields_setter",
+        "class Temp {
+  let calls = 0;
+  let v = 2;
+  set v(value) { this.calls += 1; }
+}
+let t = Temp();
+print t.calls;
+t.v = 5;
+print t.calls;
+",
+        &["0", "1"],
+    );
+}
+
+#[test]
+fn errors_in_field_defaults_report_the_call_site() {
+    assert_error(
+        "field_default_error",
+        "class Box {\n  let size = 1 / 0;\n}\nBox();\n",
+        &[],
+        "[Line 2] [at /] Division by zero (constructing Box, called at line 4)",
+    );
+}
+
+#[test]
+fn defaults_can_use_this_and_earlier_fields() {
+    assert_prints(
+        "fields_this",
+        "class Rect {
+  let width = 2;
+  let height = this.width * 3;
+  let area = this.computeArea();
+  let self = this;
+  computeArea() { return this.width * this.height; }
+}
+let r = Rect();
+print r.height;
+print r.area;
+print r.self == r;
+",
+        &["6", "12", "true"],
+    );
+}
+
+#[test]
+fn default_cannot_call_super_init() {
+    assert_error(
+        "fields_super_init",
+        "class Parent { init() { print \"parent\"; } }
+class Child : Parent {
+  let value = super.init();
+}
+",
+        &[],
+        "[Line 3] [at init] Cannot call super.init from a field default",
+    );
+}
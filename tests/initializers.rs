@@ -0,0 +1,105 @@
+// errors raised while constructing an instance, and what init() returns
+mod common;
+
+use common::{assert_error, assert_prints};
+
+#[test]
+fn runtime_error_in_init_reports_inner_location_and_call_site() {
+    assert_error(
+        "init_runtime_error",
+        "class Config {
+  init(settings) { this.port = settings[\"port\"]; }
+}
+print \"before\";
+Config({});
+print \"after\";
+",
+        &["before"],
+        "[Line 2] [at [] Undefined key port (constructing Config, called at line 5)",
+    );
+}
+
+#[test]
+fn thrown_value_in_init_reports_the_call_site() {
+    assert_error(
+        "init_throw",
+        "class Account {\n  init(balance) { if (balance < 0) { throw \"negative\"; } }\n}\n\nAccount(-1);\n",
+        &[],
+        "[Line 2] Uncaught exception: negative (constructing Account, called at line 5)",
+    );
+}
+
+#[test]
+fn nested_constructors_add_each_call_site() {
+    assert_error(
+        "init_nested",
+        "class Inner { init() { let x = 1 / 0; } }\nclass Outer { init() { this.inner = Inner(); } }\nOuter();\n",
+        &[],
+        "[Line 1] [at /] Division by zero (constructing Inner, called at line 2) (constructing Outer, called at line 3)",
+    );
+}
+
+#[test]
+fn init_errors_can_be_caught() {
+    assert_prints(
+        "init_caught",
+        "class Account { init(b) { if (b < 0) { throw \"negative\"; } this.b = b; } }
+try { Account(-1); } catch (e) { print e.value; }
+class Config { init(s) { this.port = s[\"port\"]; } }
+try { Config({}); } catch (e) { print e.message; print e.line; }
+",
+        &["negative", "Undefined key port", "3"],
+    );
+}
+
+#[test]
+fn failed_init_does_not_hand_out_the_instance() {
+    assert_prints(
+        "init_no_half_instance",
+        "class Box { init() { this.a = 1; throw \"stop\"; } }
+let b = null;
+try { b = Box(); } catch (e) { print e.value; }
+print b;
+",
+        &["stop", "nil"],
+    );
+}
+
+#[test]
+fn init_called_directly_returns_this() {
+    assert_prints(
+        "init_returns_this",
+        "class Counter {
+  init(start) {
+    this.count = start;
+    if (start > 100) { return; }
+  }
+}
+let c = Counter(1);
+print c.init(10) == c;
+print c.count;
+print Counter(500).init(200).count;
+print same(Counter(0).init(1), Counter(0));
+",
+        &["true", "10", "200", "false"],
+    );
+}
+
+#[test]
+fn bound_init_returns_its_instance() {
+    assert_prints(
+        "bound_init",
+        "class P { init() { this.n = 1; } }\nlet p = P();\nlet again = p.init;\nprint again() == p;\n",
+        &["true"],
+    );
+}
+
+#[test]
+fn abstract_class_errors_point_at_the_call() {
+    assert_error(
+        "abstract_call_site",
+        "class Shape { area(); }\n\nShape();\n",
+        &[],
+        "[Line 3] [at )] Cannot instantiate abstract class Shape, missing: area",
+    );
+}
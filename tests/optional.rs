@@ -0,0 +1,94 @@
+// conditional, null-coalescing and optional chaining operators
+mod common;
+
+use common::{assert_error, assert_prints};
+
+const NODES: &str = "class Node {
+  init(value, next) { this.value = value; this.next = next; }
+  describe() { return this; }
+}
+let list = Node(1, Node(2, null));
+let a = null;
+";
+
+#[test]
+fn conditional_groups_to_the_right() {
+    assert_prints(
+        "conditional",
+        "let n = 0;\nprint n < 0 ? \"negative\" : n == 0 ? \"zero\" : \"positive\";\nprint 1 ? 2 : 3;\n",
+        &["zero", "2"],
+    );
+}
+
+#[test]
+fn coalesce_only_replaces_null() {
+    assert_prints(
+        "coalesce",
+        "print null ?? \"fallback\";\nprint 0 ?? \"fallback\";\nprint false ?? true;\nprint null ?? null ?? 3;\n",
+        &["fallback", "0", "false", "3"],
+    );
+}
+
+#[test]
+fn optional_get_on_null_is_null() {
+    assert_prints(
+        "optional_get",
+        &format!("{}print a?.b;\nprint list.next?.value;\nprint list.next.next?.value ?? -1;\n", NODES),
+        &["nil", "2", "-1"],
+    );
+}
+
+#[test]
+fn null_short_circuits_the_rest_of_the_chain() {
+    assert_prints(
+        "chain_get",
+        &format!("{}print a?.b.c;\nprint a?.b.c.d ?? \"none\";\nprint list.next.next?.next.value;\n", NODES),
+        &["nil", "none", "nil"],
+    );
+}
+
+#[test]
+fn null_short_circuits_calls_in_the_chain() {
+    assert_prints(
+        "chain_call",
+        &format!("{}print a?.m().x;\nprint a?.b[0];\nprint list?.describe().value;\n", NODES),
+        &["nil", "nil", "1"],
+    );
+}
+
+#[test]
+fn skipped_calls_do_not_evaluate_arguments() {
+    assert_prints(
+        "skipped_arguments",
+        &format!(
+            "{}let calls = 0;\nfn count() {{ calls = calls + 1; return calls; }}\nprint a?.describe(count());\nprint a?.b.m(count());\nprint calls;\n",
+            NODES
+        ),
+        &["nil", "nil", "0"],
+    );
+}
+
+#[test]
+fn plain_get_after_a_non_null_optional_still_checks() {
+    assert_error(
+        "non_null_then_null",
+        &format!("{}print list?.next.next.value;\n", NODES),
+        &[],
+        "[Line 7] [at value] Only instances and classes have properties",
+    );
+}
+
+#[test]
+fn grouping_ends_the_chain() {
+    assert_error(
+        "grouped_chain",
+        "let a = null;\nprint (a?.b).c;\n",
+        &[],
+        "[Line 2] [at c] Only instances and classes have properties",
+    );
+}
+
+#[test]
+fn optional_chain_is_not_assignable() {
+    assert_error("assign_chain", "let a = null;\na?.b = 1;\n", &[], "[Line 2] [at =] Invalid assignment target");
+}
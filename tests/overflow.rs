@@ -0,0 +1,69 @@
+// integer overflow has to be the same runtime error in debug and release builds,
+// run these with both `cargo test` and `cargo test --release`
+mod common;
+
+use common::{assert_error, assert_prints};
+
+const MIN: &str = "let min = -2147483647 - 1;\n";
+
+#[test]
+fn in_range_arithmetic_is_exact() {
+    assert_prints(
+        "in_range",
+        "let max = 2147483647;\nlet min = -max - 1;\nprint max;\nprint min;\nprint min / 2;\nprint 7 % -3;\nprint max + 1.0;\n",
+        &["2147483647", "-2147483648", "-1073741824", "1", "2147483648.0"],
+    );
+}
+
+#[test]
+fn addition_overflows() {
+    assert_error("add", "print 2147483647 + 1;\n", &[], "[Line 1] [at +] Integer overflow");
+}
+
+#[test]
+fn subtraction_overflows() {
+    assert_error("sub", &format!("{}print min - 1;\n", MIN), &[], "[Line 2] [at -] Integer overflow");
+}
+
+#[test]
+fn multiplication_overflows() {
+    assert_error("mul", "print 65536 * 65536;\n", &[], "[Line 1] [at *] Integer overflow");
+}
+
+#[test]
+fn division_overflows() {
+    assert_error("div", &format!("{}print min / -1;\n", MIN), &[], "[Line 2] [at /] Integer overflow");
+}
+
+#[test]
+fn modulo_of_min_by_minus_one_overflows() {
+    assert_error("rem", &format!("{}print min % -1;\n", MIN), &[], "[Line 2] [at %] Integer overflow");
+}
+
+#[test]
+fn negating_min_overflows() {
+    assert_error("neg", &format!("{}print -min;\n", MIN), &[], "[Line 2] [at -] Integer overflow");
+}
+
+#[test]
+fn division_by_zero_is_an_error() {
+    assert_error("div_zero", "print 1 / 0;\n", &[], "[Line 1] [at /] Division by zero");
+    assert_error("rem_zero", "print 1 % 0;\n", &[], "[Line 1] [at %] Modulo by zero");
+}
+
+#[test]
+fn update_operators_overflow() {
+    let max = "let max = 2147483647;\n";
+    assert_error("compound", &format!("{}max += 1;\n", max), &[], "[Line 2] [at +=] Integer overflow");
+    assert_error("increment", &format!("{}max++;\n", max), &[], "[Line 2] [at ++] Integer overflow");
+}
+
+#[test]
+fn output_before_the_overflow_is_kept() {
+    assert_error(
+        "partial",
+        "print 1;\nprint 2147483647 + 1;\nprint 2;\n",
+        &["1"],
+        "[Line 2] [at +] Integer overflow",
+    );
+}
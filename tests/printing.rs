@@ -0,0 +1,55 @@
+// how values look when printed or concatenated
+mod common;
+
+use common::{assert_error, assert_prints};
+
+#[test]
+fn callables_print_name_and_arity() {
+    assert_prints(
+        "callables",
+        "fn add(a, b) { return a + b; }\nclass Point {}\nprint add;\nprint fn (x) { return x; };\nprint Point;\nprint Point();\nprint len;\nprint clock;\n",
+        &["<fn add/2>", "<fn anonymous/1>", "<class Point>", "<Point instance>", "<native fn len/1>", "<native fn clock/0>"],
+    );
+}
+
+#[test]
+fn to_string_is_used_by_print_and_concatenation() {
+    assert_prints(
+        "to_string",
+        "class P { init(x) { this.x = x; } toString() { return \"P(\" + this.x + \")\"; } }\nprint P(1);\nprint \"at \" + P(2);\nprint [P(3)];\nprint {\"k\": P(4)};\n",
+        &["P(1)", "at P(2)", "[P(3)]", "{k: P(4)}"],
+    );
+}
+
+#[test]
+fn to_string_must_return_a_string() {
+    assert_error(
+        "to_string_type",
+        "class P { toString() { return 1; } }\nprint P();\n",
+        &[],
+        "P.toString() must return a string, got 1",
+    );
+}
+
+#[test]
+fn self_containing_list_prints_once() {
+    assert_prints(
+        "list_cycle",
+        "let l = [1, 2];\nl[1] = l;\nprint l;\nprint \"l = \" + l;\nprint [l];\n",
+        &["[1, [...]]", "l = [1, [...]]", "[[1, [...]]]"],
+    );
+}
+
+#[test]
+fn self_containing_map_prints_once() {
+    assert_prints(
+        "map_cycle",
+        "let m = {\"a\": 1};\nm[\"self\"] = m;\nlet l = [m];\nm[\"list\"] = l;\nprint m;\nprint l;\n",
+        &["{a: 1, self: {...}, list: [{...}]}", "[{a: 1, self: {...}, list: [...]}]"],
+    );
+}
+
+#[test]
+fn shared_values_that_are_not_cycles_print_in_full() {
+    assert_prints("shared", "let s = [0];\nprint [s, s];\n", &["[[0], [0]]"]);
+}
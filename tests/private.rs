@@ -0,0 +1,188 @@
+// `#name` members, only reachable from the class that declares them
+mod common;
+
+use common::{assert_error, assert_prints};
+
+#[test]
+fn private_members_work_inside_their_class() {
+    assert_prints(
+        "private_inside",
+        "class Account {
+  let #balance = 0;
+  deposit(amount) { this.#check(amount); this.#balance += amount; return this; }
+  #check(amount) { if (amount < 0) { throw \"negative\"; } }
+  balance() { return this.#balance; }
+}
+let a = Account();
+print a.deposit(5).balance();
+print fields(a);
+",
+        &["5", "[]"],
+    );
+}
+
+#[test]
+fn access_from_outside_the_class_is_rejected() {
+    assert_error(
+        "private_outside",
+        "class A { let #x = 1; }\nprint A().#x;\n",
+        &[],
+        "[Line 2] [at #x] Private member #x is not declared in the enclosing class",
+    );
+}
+
+#[test]
+fn nested_class_cannot_reach_the_outer_class_private_members() {
+    assert_error(
+        "private_nested_class",
+        "class Outer {
+  let #secret = 1;
+  make() {
+    class Inner {
+      peek(o) { return o.#secret; }
+    }
+    return Inner();
+  }
+}
+print \"start\";
+print Outer().make().peek(Outer());
+",
+        &[],
+        "[Line 5] [at #secret] Private member #secret is not declared in the enclosing class",
+    );
+}
+
+#[test]
+fn closure_in_a_method_can_reach_private_members() {
+    assert_prints(
+        "private_closure",
+        "class Counter {
+  let #count = 0;
+  incrementer() {
+    fn increment() { this.#count += 1; return this.#count; }
+    return increment;
+  }
+}
+let inc = Counter().incrementer();
+inc();
+print inc();
+",
+        &["2"],
+    );
+}
+
+#[test]
+fn private_name_cannot_be_a_top_level_variable() {
+    assert_error(
+        "private_let_top_level",
+        "let #x = 3;\nprint #x;\n",
+        &[],
+        "[Line 1] [at #x] Private name #x can only be used as a class member, like this.#x",
+    );
+}
+
+#[test]
+fn private_name_cannot_be_read_as_a_variable() {
+    assert_error(
+        "private_variable",
+        "print \"start\";\nprint #x;\n",
+        &[],
+        "[Line 2] [at #x] Private name #x can only be used as a class member, like this.#x",
+    );
+}
+
+#[test]
+fn private_name_cannot_be_a_local_in_a_free_function() {
+    assert_error(
+        "private_let_function",
+        "fn f() {\n  let #y = 1;\n  return #y;\n}\n",
+        &[],
+        "[Line 2] [at #y] Private name #y can only be used as a class member, like this.#y",
+    );
+}
+
+#[test]
+fn private_name_cannot_be_a_parameter_or_function() {
+    assert_error(
+        "private_param",
+        "fn g(#p) { return 1; }\n",
+        &[],
+        "[Line 1] [at #p] Private name #p can only be used as a class member, like this.#p",
+    );
+    assert_error(
+        "private_function",
+        "fn #h() {}\n",
+        &[],
+        "[Line 1] [at #h] Private name #h can only be used as a class member, like this.#h",
+    );
+}
+
+#[test]
+fn private_name_cannot_be_a_local_inside_a_method() {
+    assert_error(
+        "private_let_method",
+        "class A {\n  let #z = 0;\n  m() { let #z = 1; return this.#z; }\n}\n",
+        &[],
+        "[Line 3] [at #z] Private name #z can only be used as a class member, like this.#z",
+    );
+}
+
+#[test]
+fn parent_and_subclass_fields_of_the_same_name_are_separate() {
+    assert_prints(
+        "private_inherited_fields",
+        "class Base {
+  let #x = \"base\";
+  baseX() { return this.#x; }
+  setBase(v) { this.#x = v; }
+}
+class Derived : Base {
+  let #x = \"derived\";
+  derivedX() { return this.#x; }
+}
+let d = Derived();
+print d.baseX();
+print d.derivedX();
+d.setBase(\"changed\");
+print d.baseX();
+print d.derivedX();
+",
+        &["base", "derived", "changed", "derived"],
+    );
+}
+
+#[test]
+fn parent_code_calls_its_own_private_method() {
+    assert_prints(
+        "private_inherited_methods",
+        "class Base {
+  #who() { return \"Base\"; }
+  callWho() { return this.#who(); }
+}
+class Derived : Base {
+  #who() { return \"Derived\"; }
+  callMine() { return this.#who(); }
+}
+let d = Derived();
+print d.callWho();
+print d.callMine();
+",
+        &["Base", "Derived"],
+    );
+}
+
+#[test]
+fn subclass_cannot_reach_parent_private_members() {
+    assert_error(
+        "private_subclass_access",
+        "class Base { let #x = 1; }
+class Derived : Base {
+  let #y = 2;
+  peek(other) { return other.#y; }
+}
+print Derived().peek(Base());
+",
+        &[],
+        "[Line 4] [at #y] Cannot access private member #y of Base from outside its class",
+    );
+}
@@ -0,0 +1,124 @@
+// update operators (x++, ++x, x += n) must write to the binding the resolver picked,
+// the same one a plain read or assignment at that spot would use
+mod common;
+
+use common::assert_prints;
+
+#[test]
+fn closure_keeps_updating_the_captured_global_after_shadowing() {
+    assert_prints(
+        "shadowed_after_capture",
+        "let count = 0;
+{
+  fn bump() { count++; ++count; count += 1; }
+  bump();
+  let count = 100;
+  bump();
+  print count;
+}
+print count;
+",
+        &["100", "6"],
+    );
+}
+
+#[test]
+fn each_closure_updates_its_own_counter() {
+    assert_prints(
+        "own_counter",
+        "fn counter() {
+  let n = 0;
+  return fn () { n++; return n; };
+}
+let a = counter();
+let b = counter();
+a(); a();
+print a();
+print b();
+",
+        &["3", "1"],
+    );
+}
+
+#[test]
+fn parameter_shadows_the_global_it_is_named_after() {
+    assert_prints(
+        "parameter_shadow",
+        "let total = 10;
+fn add(total) {
+  total += 5;
+  return total++;
+}
+print add(1);
+print total;
+",
+        &["6", "10"],
+    );
+}
+
+#[test]
+fn inner_block_update_leaves_outer_variable_alone() {
+    assert_prints(
+        "block_shadow",
+        "let x = 1;
+{
+  let x = 10;
+  x++;
+  x -= 3;
+  print x;
+}
+x--;
+print x;
+",
+        &["8", "0"],
+    );
+}
+
+#[test]
+fn nested_closures_update_the_enclosing_function_variable() {
+    assert_prints(
+        "nested_closure",
+        "fn outer() {
+  let n = 0;
+  fn middle() {
+    fn inner() { n += 10; }
+    inner();
+    n++;
+  }
+  middle();
+  let n2 = n;
+  return n2;
+}
+let n = 500;
+print outer();
+print n;
+",
+        &["11", "500"],
+    );
+}
+
+#[test]
+fn prefix_and_postfix_results() {
+    assert_prints(
+        "prefix_postfix",
+        "let i = 5;\nprint i++;\nprint ++i;\nprint i--;\nprint --i;\n",
+        &["5", "7", "7", "5"],
+    );
+}
+
+#[test]
+fn fields_and_elements_update_in_place() {
+    assert_prints(
+        "fields_elements",
+        "class Box { init() { this.value = 1.5; } }
+let box = Box();
+box.value++;
+print box.value;
+let xs = [0, 0];
+xs[1]++;
+xs[0] += 4;
+print xs;
+",
+        &["2.5", "[4, 1]"],
+    );
+}